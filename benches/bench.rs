@@ -1,11 +1,53 @@
+//! Benchmarks for every registered day and every input in `data/`.
+//!
+//! Each input gets its own group `y{year}/d{day}/{suffix}` with separate
+//! benchmarks for parsing and the two subtasks, so a single day can be
+//! selected with e.g. `cargo bench -- y2021/d05/full`. Days which compute
+//! both subtasks in one `solve` call only get a single `solve` benchmark.
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
-use advent::{io, y2021};
+use advent::{answers, io, solution};
+
+const DATA_DIR: &str = "data";
 
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("solve", |b| {
-        b.iter(|| y2021::d22::solve(&io::file_str(black_box("data/y2021/d22_full.txt")).unwrap()))
-    });
+    for year in answers::years(DATA_DIR).unwrap() {
+        for input in answers::inputs(DATA_DIR, year).unwrap() {
+            let entry = match solution::find(year, input.day) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let data = io::file_str(&input.path).unwrap();
+
+            let mut group =
+                c.benchmark_group(format!("y{}/d{:02}/{}", year, input.day, input.suffix));
+            group.sample_size(10);
+
+            if !entry.split {
+                group.bench_function("solve", |b| {
+                    b.iter(|| entry.solve(black_box(&data)).unwrap())
+                });
+                group.finish();
+                continue;
+            }
+
+            group.bench_function("parse", |b| {
+                b.iter(|| entry.parse(black_box(&data)).unwrap())
+            });
+
+            // The parts don't modify the parsed input, so it can be reused.
+            let parsed = entry.parse(&data).unwrap();
+            group.bench_function("part1", |b| {
+                b.iter(|| entry.part1(black_box(&parsed)).unwrap())
+            });
+            group.bench_function("part2", |b| {
+                b.iter(|| entry.part2(black_box(&parsed)).unwrap())
+            });
+
+            group.finish();
+        }
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
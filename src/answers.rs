@@ -0,0 +1,190 @@
+//! Recorded answers for the inputs in `data/`.
+//!
+//! Every year has a manifest at `data/{year}/answers.toml` with one table per
+//! day and input suffix, both subtasks are optional:
+//!
+//! ```toml
+//! [d01.example]
+//! part1 = 3
+//! part2 = 6
+//!
+//! [d13.full]
+//! part1 = 818
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use toml::Value;
+
+/// The expected answers for a single input file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expected {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// All recorded answers of a year, keyed by day and input suffix.
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    pub year: u32,
+    pub answers: BTreeMap<(u32, String), Expected>,
+}
+
+/// An input file following the `data/{year}/d{day}_{suffix}.txt` convention.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Input {
+    pub year: u32,
+    pub day: u32,
+    pub suffix: String,
+    pub path: PathBuf,
+}
+
+fn answer(value: &Value, key: &str) -> Result<String> {
+    match value {
+        Value::Integer(n) => Ok(n.to_string()),
+        Value::String(s) => Ok(s.clone()),
+        _ => bail!("{} has to be an integer or a string", key),
+    }
+}
+
+fn parse_day(name: &str) -> Option<u32> {
+    name.strip_prefix('d')?.parse().ok()
+}
+
+impl Manifest {
+    pub fn parse(year: u32, manifest: &str) -> Result<Manifest> {
+        let table: toml::value::Table = toml::from_str(manifest)?;
+
+        let mut answers = BTreeMap::new();
+        for (day_name, inputs) in table {
+            let day = parse_day(&day_name).ok_or_else(|| anyhow!("Invalid day '{}'", day_name))?;
+            let inputs = inputs
+                .as_table()
+                .ok_or_else(|| anyhow!("{} has to be a table of inputs", day_name))?;
+
+            for (suffix, parts) in inputs {
+                let key = format!("{}.{}", day_name, suffix);
+                let parts = parts
+                    .as_table()
+                    .ok_or_else(|| anyhow!("{} has to be a table of answers", key))?;
+
+                let mut expected = Expected::default();
+                for (part, value) in parts {
+                    let value = answer(value, &format!("{}.{}", key, part))?;
+                    match part.as_str() {
+                        "part1" => expected.part1 = Some(value),
+                        "part2" => expected.part2 = Some(value),
+                        _ => bail!("Unknown key {}.{}", key, part),
+                    }
+                }
+                answers.insert((day, suffix.clone()), expected);
+            }
+        }
+
+        Ok(Manifest { year, answers })
+    }
+
+    /// Load `data/y{year}/answers.toml`. A missing manifest is treated as
+    /// empty, so that a new year only needs its input files to get started.
+    pub fn load(data_dir: impl AsRef<Path>, year: u32) -> Result<Manifest> {
+        let path = data_dir
+            .as_ref()
+            .join(format!("y{}", year))
+            .join("answers.toml");
+        if !path.is_file() {
+            return Ok(Manifest {
+                year,
+                ..Default::default()
+            });
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        Manifest::parse(year, &content).with_context(|| format!("Cannot parse {:?}", path))
+    }
+
+    pub fn get(&self, day: u32, suffix: &str) -> Option<&Expected> {
+        self.answers.get(&(day, suffix.to_owned()))
+    }
+}
+
+/// The years for which a `data/y{year}` directory exists.
+pub fn years(data_dir: impl AsRef<Path>) -> Result<Vec<u32>> {
+    let mut years = vec![];
+    for entry in std::fs::read_dir(data_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let year = name
+            .to_str()
+            .and_then(|n| n.strip_prefix('y')?.parse().ok());
+        if let (Some(year), true) = (year, entry.file_type()?.is_dir()) {
+            years.push(year);
+        }
+    }
+    years.sort_unstable();
+    Ok(years)
+}
+
+/// All input files of a year. Files that don't follow the naming
+/// convention, like notes or scripts, are ignored.
+pub fn inputs(data_dir: impl AsRef<Path>, year: u32) -> Result<Vec<Input>> {
+    let dir = data_dir.as_ref().join(format!("y{}", year));
+    let mut inputs = vec![];
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("Cannot list {:?}", dir))? {
+        let path = entry?.path();
+        let stem = match (path.file_stem(), path.extension()) {
+            (Some(stem), Some(ext)) if ext == "txt" => stem.to_string_lossy().into_owned(),
+            _ => continue,
+        };
+        let (day, suffix) = match stem.split_once('_') {
+            Some((day, suffix)) => (day, suffix),
+            None => continue,
+        };
+        if let Some(day) = parse_day(day) {
+            inputs.push(Input {
+                year,
+                day,
+                suffix: suffix.to_owned(),
+                path,
+            });
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() -> Result<()> {
+        let manifest = Manifest::parse(
+            2021,
+            r#"
+                [d13.example]
+                part1 = 17
+                part2 = "O"
+
+                [d22.example_1]
+                part1 = 39
+            "#,
+        )?;
+
+        assert_eq!(
+            manifest.get(13, "example"),
+            Some(&Expected {
+                part1: Some("17".to_owned()),
+                part2: Some("O".to_owned()),
+            })
+        );
+        assert_eq!(manifest.get(22, "example_1").unwrap().part2, None);
+        assert_eq!(manifest.get(22, "full"), None);
+
+        assert!(Manifest::parse(2021, "[d01.full]\npart3 = 1").is_err());
+        assert!(Manifest::parse(2021, "[day1.full]\npart1 = 1").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,307 @@
+//! Cellular automata on top of [Grid] and [SparseGrid].
+//!
+//! A generation consists of one or more synchronous updates with
+//! [Automaton::apply], where every cell is computed from the previous state
+//! of itself and its neighbours, so the order in which the cells are visited
+//! doesn't matter.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::grid::{Grid, NEIGHBOURS_4, NEIGHBOURS_8};
+use crate::sparse_grid::SparseGrid;
+
+/// Which cells count as the neighbours of a cell.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Neighbourhood {
+    /// The 8 surrounding cells.
+    Moore,
+    /// The 4 orthogonally adjacent cells.
+    VonNeumann,
+    /// An explicit list of offsets, e.g. only the cell to the left. The
+    /// offsets may include `(0, 0)` to see the cell itself.
+    Directional(&'static [(i64, i64)]),
+}
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> &'static [(i64, i64)] {
+        match self {
+            Neighbourhood::Moore => &NEIGHBOURS_8,
+            Neighbourhood::VonNeumann => &NEIGHBOURS_4,
+            Neighbourhood::Directional(offsets) => offsets,
+        }
+    }
+
+    /// How far the furthest neighbour is away, in either direction.
+    pub fn radius(&self) -> i64 {
+        self.offsets()
+            .iter()
+            .map(|(dx, dy)| dx.abs().max(dy.abs()))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// What happens to neighbours beyond the edge of a dense grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Edges {
+    /// Cells outside of the grid don't exist.
+    Bounded,
+    /// The grid is a torus, leaving on one side enters on the other.
+    Wrapping,
+}
+
+enum Source<'a, T> {
+    Dense(&'a Grid<T>, Edges),
+    Sparse(&'a SparseGrid<T>),
+    Uniform(&'a T),
+}
+
+/// The neighbours of a single cell, as seen by a rule.
+pub struct Neighbours<'a, T> {
+    source: Source<'a, T>,
+    pos: (i64, i64),
+    offsets: &'static [(i64, i64)],
+}
+
+impl<'a, T: Clone + PartialEq> Neighbours<'a, T> {
+    /// Position of the cell whose neighbours these are.
+    pub fn pos(&self) -> (i64, i64) {
+        self.pos
+    }
+
+    /// The neighbour at the `i`-th offset of the neighbourhood, `None` if
+    /// it is beyond a bounded edge.
+    pub fn get(&self, i: usize) -> Option<&'a T> {
+        let (dx, dy) = self.offsets[i];
+        let (x, y) = (self.pos.0 + dx, self.pos.1 + dy);
+        match self.source {
+            Source::Dense(grid, Edges::Bounded) => grid.get_signed(x, y),
+            Source::Dense(grid, Edges::Wrapping) => grid.get_wrapping(x, y),
+            Source::Sparse(grid) => Some(grid.get((x, y))),
+            Source::Uniform(value) => Some(value),
+        }
+    }
+
+    /// The existing neighbours in the order of the neighbourhood's offsets.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        (0..self.offsets.len()).filter_map(|i| self.get(i))
+    }
+
+    pub fn count(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.iter().filter(|v| pred(v)).count()
+    }
+}
+
+/// Where a cycle found by [Automaton::find_cycle] starts and how long it is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// A cellular automaton on a dense grid, double-buffered so that updates
+/// don't allocate.
+pub struct Automaton<T> {
+    grid: Grid<T>,
+    next: Grid<T>,
+    edges: Edges,
+    generation: usize,
+}
+
+impl<T: Clone + PartialEq> Automaton<T> {
+    pub fn new(grid: Grid<T>, edges: Edges) -> Automaton<T> {
+        Automaton {
+            next: grid.clone(),
+            grid,
+            edges,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.grid
+    }
+
+    /// Number of generations run with [Self::advance] and friends.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Update every cell at once with `rule`, which gets the current value
+    /// and the neighbours of the cell. Returns the number of changed cells.
+    pub fn apply(
+        &mut self,
+        neighbourhood: &Neighbourhood,
+        mut rule: impl FnMut(&T, &Neighbours<T>) -> T,
+    ) -> usize {
+        let offsets = neighbourhood.offsets();
+        let mut changed = 0;
+        for ((x, y), next) in self.next.iter_mut() {
+            let neighbours = Neighbours {
+                source: Source::Dense(&self.grid, self.edges),
+                pos: (x as i64, y as i64),
+                offsets,
+            };
+            let current = &self.grid[(x, y)];
+            *next = rule(current, &neighbours);
+            if next != current {
+                changed += 1;
+            }
+        }
+        std::mem::swap(&mut self.grid, &mut self.next);
+        changed
+    }
+
+    /// Update every cell independently of its neighbours. Returns the number
+    /// of changed cells.
+    pub fn map(&mut self, mut f: impl FnMut(&T) -> T) -> usize {
+        let mut changed = 0;
+        for value in self.grid.values_mut() {
+            let new = f(value);
+            if new != *value {
+                changed += 1;
+                *value = new;
+            }
+        }
+        changed
+    }
+
+    /// Run a single generation. `step` is expected to return the number of
+    /// changed cells, which is passed through.
+    pub fn advance(&mut self, mut step: impl FnMut(&mut Self) -> usize) -> usize {
+        let changed = step(self);
+        self.generation += 1;
+        changed
+    }
+
+    /// Run generations until one of them doesn't change anything, and return
+    /// the number of that generation.
+    pub fn run_until_stable(&mut self, mut step: impl FnMut(&mut Self) -> usize) -> usize {
+        while self.advance(&mut step) > 0 {}
+        self.generation
+    }
+}
+
+impl<T: Clone + Eq + Hash> Automaton<T> {
+    /// Run generations until a state repeats. Afterwards the automaton is in
+    /// the state at which the cycle starts, one period later.
+    pub fn find_cycle(&mut self, mut step: impl FnMut(&mut Self) -> usize) -> Cycle {
+        let mut seen = HashMap::new();
+        loop {
+            if let Some(start) = seen.insert(self.grid.clone(), self.generation) {
+                return Cycle {
+                    start,
+                    length: self.generation - start,
+                };
+            }
+            self.advance(&mut step);
+        }
+    }
+}
+
+/// One synchronous update of a sparse grid. The rule is also applied to the
+/// background, with all neighbours being the background, to get the new
+/// background value.
+pub fn step_sparse<T: Clone + PartialEq>(
+    grid: &SparseGrid<T>,
+    neighbourhood: &Neighbourhood,
+    mut rule: impl FnMut(&T, &Neighbours<T>) -> T,
+) -> SparseGrid<T> {
+    let offsets = neighbourhood.offsets();
+    let background = grid.background();
+    let mut out = SparseGrid::new(rule(
+        background,
+        &Neighbours {
+            source: Source::Uniform(background),
+            pos: (0, 0),
+            offsets,
+        },
+    ));
+
+    if let Some(bounds) = grid.bounds() {
+        for pos in bounds.expand(neighbourhood.radius()).positions() {
+            let neighbours = Neighbours {
+                source: Source::Sparse(grid),
+                pos,
+                offsets,
+            };
+            out.set(pos, rule(grid.get(pos), &neighbours));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(alive: &bool, neighbours: &Neighbours<bool>) -> bool {
+        matches!((alive, neighbours.count(|&n| n)), (true, 2) | (_, 3))
+    }
+
+    #[test]
+    fn test_blinker() {
+        let grid = Grid::parse_with(".....\n..#..\n..#..\n..#..\n.....\n", |c| c == b'#').unwrap();
+        let mut automaton = Automaton::new(grid.clone(), Edges::Bounded);
+        assert_eq!(automaton.apply(&Neighbourhood::Moore, life), 4);
+        assert_eq!(automaton.grid().transposed(), grid);
+
+        let cycle = automaton.find_cycle(|a| a.apply(&Neighbourhood::Moore, life));
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_wrapping_glider() {
+        let grid = Grid::parse_with(".#...\n..#..\n###..\n.....\n.....\n", |c| c == b'#').unwrap();
+        let mut automaton = Automaton::new(grid, Edges::Wrapping);
+        let cycle = automaton.find_cycle(|a| a.apply(&Neighbourhood::Moore, life));
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 20
+            }
+        );
+    }
+
+    #[test]
+    fn test_until_stable() {
+        // Cells die unless they have a neighbour to their left.
+        let grid = Grid::from_rows(vec![vec![true, true, true, false, true]]).unwrap();
+        let mut automaton = Automaton::new(grid, Edges::Bounded);
+        let stable = automaton.run_until_stable(|a| {
+            a.apply(&Neighbourhood::Directional(&[(-1, 0)]), |&v, n| {
+                v && n.get(0) == Some(&true)
+            })
+        });
+        assert_eq!(stable, 4);
+        assert_eq!(automaton.grid().count(|&v| v), 0);
+    }
+
+    #[test]
+    fn test_sparse_background() {
+        // Cells turn on if none of their neighbours are on, so the
+        // background toggles while the cell in the middle switches off its
+        // neighbours.
+        let mut grid = SparseGrid::new(false);
+        grid.set((0, 0), true);
+        let rule = |_: &bool, n: &Neighbours<bool>| n.count(|&v| v) == 0;
+
+        let grid = step_sparse(&grid, &Neighbourhood::VonNeumann, rule);
+        assert!(*grid.background());
+        assert_eq!(grid.count(|&v| !v), 4);
+        assert!(*grid.get((0, 0)));
+        assert!(!*grid.get((1, 0)));
+    }
+}
@@ -1,135 +1,30 @@
-// I can see why someone would have thought this lint
-// would be a good idea, but.. what if I want to
-// implement Mul for a polynomial??
-#![allow(clippy::suspicious_arithmetic_impl)]
-
-use auto_ops::impl_op_ex;
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::fmt;
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct BivariatePolynomial {
-    coeffs: HashMap<(u16, u16), usize>,
-}
+//! Polynomials in the two variables `x` and `y` with integer coefficients,
+//! a thin layer over [Polynomial] for code written before it existed.
+
+use crate::polynomial::{Monomial, Polynomial};
+
+pub type BivariatePolynomial = Polynomial<i64>;
 
 pub type BP = BivariatePolynomial;
 
 impl BivariatePolynomial {
-    pub fn new(list: &[((u16, u16), usize)]) -> Self {
-        BivariatePolynomial {
-            coeffs: list.iter().filter(|(_, v)| v != &0).cloned().collect(),
-        }
-    }
-
-    pub fn constant(n: usize) -> Self {
-        Self::new(&[((0, 0), n)])
+    pub fn new(list: &[((u16, u16), i64)]) -> Self {
+        Polynomial::from_terms(
+            list.iter()
+                .map(|&((x, y), v)| (Monomial::new(&[("x", x as u32), ("y", y as u32)]), v)),
+        )
     }
 
     pub fn x() -> Self {
-        Self::new(&[((1, 0), 1)])
+        Polynomial::var("x")
     }
 
     pub fn y() -> Self {
-        Self::new(&[((0, 1), 1)])
-    }
-
-    pub fn get_constant(&self) -> Option<usize> {
-        if let Some((k, v)) = self.coeffs.iter().next() {
-            if self.coeffs.len() == 1 && *k == (0, 0) {
-                Some(*v)
-            } else {
-                // Is not constant.
-                None
-            }
-        } else {
-            // No coefficients available => constant zero.
-            Some(0)
-        }
+        Polynomial::var("y")
     }
 
-    // Also returns true for constants.
-    pub fn is_linear(&self) -> bool {
-        for ((x, y), v) in self.coeffs.iter() {
-            if *v != 0 && x + y > 1 {
-                return false;
-            }
-        }
-
-        true
-    }
-
-    pub fn evaluate(&self, x: usize, y: usize) -> usize {
-        let mut out: usize = 0;
-        // Not using Horner's scheme here because the distribution
-        // of non-zero terms might be sparse.
-        for ((px, py), v) in self.coeffs.iter() {
-            out += x.pow(*px as u32) * y.pow(*py as u32) * v;
-        }
-
-        out
-    }
-
-    pub fn get_coeff(&self, pow_x: u16, pow_y: u16) -> usize {
-        *self.coeffs.get(&(pow_x, pow_y)).unwrap_or(&0)
-    }
-}
-
-impl_op_ex!(+ |a: &BivariatePolynomial, b: &BivariatePolynomial| -> BivariatePolynomial {
-    let mut out = a.clone();
-
-    for (k, v) in &b.coeffs {
-        *out.coeffs.entry(*k).or_insert(0) += v;
-    };
-
-    out
-});
-
-// I guess rustfmt detects the * as a deref, which tells me that maybe
-// the impl_op_ex macro in its current form is not ideal. Anyway, I
-// would like to keep that space between `* |` thank you very much.
-#[rustfmt::skip]
-impl_op_ex!(* |a: &BivariatePolynomial, b: &BivariatePolynomial| -> BivariatePolynomial {
-        let mut out = BivariatePolynomial::new(&[]);
-
-        for ((xa, ya), va) in &a.coeffs {
-            for ((xb, yb), vb) in &b.coeffs {
-                let k = (xa + xb, ya + yb);
-                *out.coeffs.entry(k).or_insert(0) += va * vb;
-            }
-        }
-
-        out
-    }
-);
-
-const SUPERSCRIPT: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
-
-fn superscript_number(num: u16) -> String {
-    num.to_string()
-        .chars()
-        .map(|c| SUPERSCRIPT[c.to_digit(10).unwrap() as usize])
-        .collect()
-}
-
-impl fmt::Display for BivariatePolynomial {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "(")?;
-
-        for (i, ((x, y), v)) in self.coeffs.iter().sorted().enumerate() {
-            if i > 0 {
-                write!(f, " + ")?;
-            }
-            write!(
-                f,
-                "{} x{}y{}",
-                v,
-                superscript_number(*x),
-                superscript_number(*y)
-            )?;
-        }
-
-        write!(f, ")")
+    pub fn get_coeff(&self, pow_x: u16, pow_y: u16) -> i64 {
+        self.coeff(&Monomial::new(&[("x", pow_x as u32), ("y", pow_y as u32)]))
     }
 }
 
@@ -178,11 +73,11 @@ mod tests {
         let x = BP::new(&[((1, 0), 1)]);
         let y = BP::new(&[((0, 1), 1)]);
 
-        assert_eq!(format!("{}", &zero), "()");
-        assert_eq!(format!("{}", &one), "(1 x⁰y⁰)");
-        assert_eq!(format!("{}", &x), "(1 x¹y⁰)");
-        assert_eq!(format!("{}", &one + &x), "(1 x⁰y⁰ + 1 x¹y⁰)");
-        assert_eq!(format!("{}", &x * &y), "(1 x¹y¹)");
+        assert_eq!(format!("{}", &zero), "0");
+        assert_eq!(format!("{}", &one), "1");
+        assert_eq!(format!("{}", &x), "x");
+        assert_eq!(format!("{}", &one + &x), "x + 1");
+        assert_eq!(format!("{}", &x * &y), "xy");
     }
 
     #[test]
@@ -198,12 +93,6 @@ mod tests {
         assert_eq!(y, BP::y());
     }
 
-    #[test]
-    fn test_superscript() {
-        assert_eq!(superscript_number(0), "⁰");
-        assert_eq!(superscript_number(123), "¹²³");
-    }
-
     #[test]
     fn test_get_constant() {
         assert_eq!(BP::constant(0).get_constant(), Some(0));
@@ -223,8 +112,12 @@ mod tests {
 
     #[test]
     fn test_evaluate() {
-        assert_eq!((BP::x() + BP::y()).evaluate(2, 4), 6);
-        assert_eq!((BP::x() * BP::y() + BP::y()).evaluate(2, 4), 12);
-        assert_eq!((BP::constant(3)).evaluate(2, 4), 3);
+        let at = [("x", 2), ("y", 4)];
+        assert_eq!((BP::x() + BP::y()).evaluate(&at).unwrap(), 6);
+        assert_eq!((BP::x() * BP::y() + BP::y()).evaluate(&at).unwrap(), 12);
+        assert_eq!((BP::constant(3)).evaluate(&at).unwrap(), 3);
+        assert!((BP::x() * BP::x())
+            .evaluate(&[("x", 1 << 32), ("y", 0)])
+            .is_err());
     }
 }
@@ -1,5 +1,7 @@
-use modinverse::egcd;
+use crate::number_theory::egcd;
+use anyhow::{anyhow, bail, Result};
 use nalgebra::Vector2;
+use std::ops::RangeInclusive;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Lattice {
@@ -18,21 +20,245 @@ impl Lattice {
 
         dx % sx == 0 && dy == dx / sx * sy
     }
+
+    pub fn point(&self, k: i64) -> Vector2<i64> {
+        self.offset + self.step * k
+    }
+
+    /// The values of `k` for which the solution lies in the box `x` × `y`,
+    /// `None` if there are none, if the step is zero so that `k` isn't
+    /// bounded, or if the bounds don't fit in an `i64`.
+    pub fn k_range(
+        &self,
+        x: RangeInclusive<i64>,
+        y: RangeInclusive<i64>,
+    ) -> Option<RangeInclusive<i64>> {
+        if self.step == Vector2::zeros() {
+            return None;
+        }
+        let mut k_min = i64::MIN;
+        let mut k_max = i64::MAX;
+        for (offset, step, range) in [
+            (self.offset.x, self.step.x, x),
+            (self.offset.y, self.step.y, y),
+        ] {
+            // Solve min <= offset + k * step <= max for k.
+            let min = range.start().checked_sub(offset)?;
+            let max = range.end().checked_sub(offset)?;
+            let (lo, hi) = match step.signum() {
+                0 if min <= 0 && 0 <= max => continue,
+                0 => return None,
+                1 => (div_ceil(min, step)?, div_floor(max, step)?),
+                _ => (div_ceil(max, step)?, div_floor(min, step)?),
+            };
+            k_min = k_min.max(lo);
+            k_max = k_max.min(hi);
+        }
+        (k_min <= k_max).then_some(k_min..=k_max)
+    }
+
+    /// All solutions in the box `x` × `y`.
+    pub fn solutions_in(
+        &self,
+        x: RangeInclusive<i64>,
+        y: RangeInclusive<i64>,
+    ) -> impl Iterator<Item = Vector2<i64>> + '_ {
+        self.k_range(x, y)
+            .into_iter()
+            .flatten()
+            .map(move |k| self.point(k))
+    }
+
+    /// The solution in the box `x` × `y` with the smallest value of
+    /// `objective.x * x + objective.y * y`. Ties go to the smallest `k`.
+    pub fn minimise(
+        &self,
+        x: RangeInclusive<i64>,
+        y: RangeInclusive<i64>,
+        objective: Vector2<i64>,
+    ) -> Option<Vector2<i64>> {
+        let k = self.k_range(x, y)?;
+        // The objective is linear in k, so one of the ends is optimal.
+        let slope = objective.dot(&self.step);
+        Some(self.point(if slope < 0 { *k.end() } else { *k.start() }))
+    }
+
+    /// Like [Self::minimise], but with the largest value of the objective.
+    pub fn maximise(
+        &self,
+        x: RangeInclusive<i64>,
+        y: RangeInclusive<i64>,
+        objective: Vector2<i64>,
+    ) -> Option<Vector2<i64>> {
+        self.minimise(x, y, -objective)
+    }
+}
+
+fn div_floor(a: i64, b: i64) -> Option<i64> {
+    let q = a.checked_div(b)?;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        Some(q - 1)
+    } else {
+        Some(q)
+    }
 }
 
+fn div_ceil(a: i64, b: i64) -> Option<i64> {
+    let q = a.checked_div(b)?;
+    if a % b != 0 && (a < 0) == (b < 0) {
+        Some(q + 1)
+    } else {
+        Some(q)
+    }
+}
+
+/// The solutions of `ax + by = c`. `None` if there are none, if `a` and `b`
+/// are both zero, or if the solution doesn't fit in an `i64`.
 pub fn linear_equation(a: i64, b: i64, c: i64) -> Option<Lattice> {
-    let (d, x_d, y_d) = egcd(a, b);
+    if a == 0 && b == 0 {
+        return None;
+    }
+    let (d, x_d, y_d) = egcd(a, b)?;
 
-    if c % d != 0 {
+    // The gcd can be negative, so even dividing by it can overflow.
+    if c.checked_rem(d)? != 0 {
         return None;
     }
 
+    let k = c.checked_div(d)?;
     Some(Lattice {
-        offset: [c / d * x_d, c / d * y_d].into(),
-        step: [b / d, -a / d].into(),
+        offset: [k.checked_mul(x_d)?, k.checked_mul(y_d)?].into(),
+        step: [b.checked_div(d)?, a.checked_neg()?.checked_div(d)?].into(),
     })
 }
 
+type Matrix = Vec<Vec<i64>>;
+
+/// The solutions of a system of linear equations over the integers, i.e.
+/// `offset` plus any integer combination of the `basis` vectors.
+#[derive(Debug, PartialEq, Eq)]
+pub struct IntegerLattice {
+    pub offset: Vec<i64>,
+    pub basis: Vec<Vec<i64>>,
+}
+
+impl IntegerLattice {
+    pub fn point(&self, coefficients: &[i64]) -> Vec<i64> {
+        let mut out = self.offset.clone();
+        for (c, v) in coefficients.iter().zip(&self.basis) {
+            for (o, v) in out.iter_mut().zip(v) {
+                *o += c * v;
+            }
+        }
+        out
+    }
+}
+
+/// The column-style Hermite normal form of the `m` × `n` matrix `a`, given
+/// as rows. Returns `(h, u)` with `h = a * u` and `u` unimodular, where `h`
+/// is lower triangular with positive pivots, every entry left of a pivot is
+/// reduced modulo the pivot and the columns without pivot are zero. The
+/// pivot columns come first. Fails if an entry overflows.
+pub fn hermite_normal_form(a: &[Vec<i64>]) -> Result<(Matrix, Matrix)> {
+    let n = a.first().map_or(0, |row| row.len());
+    let mut h = a.to_vec();
+    let mut u: Matrix = (0..n)
+        .map(|i| (0..n).map(|j| (i == j) as i64).collect())
+        .collect();
+    let overflow = || anyhow!("Overflow computing the Hermite normal form");
+
+    // Replace columns i and j with p * i + q * j and r * i + s * j.
+    let combine = |m: &mut Matrix, i: usize, j: usize, [p, q, r, s]: [i64; 4]| {
+        let dot = |x: i64, y: i64, a: i64, b: i64| x.checked_mul(a)?.checked_add(y.checked_mul(b)?);
+        for row in m.iter_mut() {
+            let (a, b) = (row[i], row[j]);
+            row[i] = dot(p, q, a, b).ok_or_else(overflow)?;
+            row[j] = dot(r, s, a, b).ok_or_else(overflow)?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+
+    let mut pivot = 0;
+    for row in 0..h.len() {
+        if pivot == n {
+            break;
+        }
+        for j in pivot + 1..n {
+            let (a, b) = (h[row][pivot], h[row][j]);
+            if b == 0 {
+                continue;
+            }
+            // a * p + b * q = g, the determinant p * a/g + q * b/g is 1.
+            let (g, p, q) = egcd(a, b).ok_or_else(overflow)?;
+            let ops = [p, q, b.checked_neg().ok_or_else(overflow)? / g, a / g];
+            combine(&mut h, pivot, j, ops)?;
+            combine(&mut u, pivot, j, ops)?;
+        }
+
+        let d = h[row][pivot];
+        if d == 0 {
+            continue;
+        }
+        if d < 0 {
+            for row in h.iter_mut().chain(u.iter_mut()) {
+                row[pivot] = row[pivot].checked_neg().ok_or_else(overflow)?;
+            }
+        }
+        let d = d.checked_abs().ok_or_else(overflow)?;
+        for c in 0..pivot {
+            let f = div_floor(h[row][c], d).ok_or_else(overflow)?;
+            let ops = [1, f.checked_neg().ok_or_else(overflow)?, 0, 1];
+            combine(&mut h, c, pivot, ops)?;
+            combine(&mut u, c, pivot, ops)?;
+        }
+        pivot += 1;
+    }
+    Ok((h, u))
+}
+
+/// Solve `a * x = b` over the integers, with `a` given as rows. `None` if
+/// there is no integer solution, fails if an intermediate value overflows.
+pub fn linear_system(a: &[Vec<i64>], b: &[i64]) -> Result<Option<IntegerLattice>> {
+    let n = a.first().map_or(0, |row| row.len());
+    if b.len() != a.len() {
+        bail!("{} equations, but {} right-hand sides", a.len(), b.len());
+    }
+    if let Some(i) = a.iter().position(|row| row.len() != n) {
+        bail!("Row {} has {} entries instead of {}", i, a[i].len(), n);
+    }
+    let (h, u) = hermite_normal_form(a)?;
+    let overflow = || anyhow!("Overflow solving the linear system");
+    let dot = |a: &[i64], b: &[i64]| {
+        a.iter()
+            .zip(b)
+            .try_fold(0i64, |acc, (a, b)| acc.checked_add(a.checked_mul(*b)?))
+            .ok_or_else(overflow)
+    };
+
+    // Forward substitution for h * y = b, one pivot per row at most.
+    let mut y = vec![];
+    for (row, &b) in h.iter().zip(b) {
+        let rest = b.checked_sub(dot(row, &y)?).ok_or_else(overflow)?;
+        match row.get(y.len()).copied().unwrap_or(0) {
+            0 if rest == 0 => {}
+            0 => return Ok(None),
+            d if rest % d == 0 => y.push(rest / d),
+            _ => return Ok(None),
+        }
+    }
+
+    let rank = y.len();
+    let column = |j: usize| u.iter().map(|row| row[j]).collect::<Vec<_>>();
+    let offset = u
+        .iter()
+        .map(|row| dot(&row[..rank], &y))
+        .collect::<Result<_>>()?;
+    Ok(Some(IntegerLattice {
+        offset,
+        basis: (rank..n).map(column).collect(),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,4 +272,84 @@ mod tests {
         assert!(!linear_equation(4, 5, 4).unwrap().is_solution(1, 2));
         assert_eq!(linear_equation(6, 9, 4), None);
     }
+
+    #[test]
+    fn test_box() {
+        // 3x + 5y = 60, i.e. (20, 0), (15, 3), (10, 6), (5, 9), (0, 12).
+        let lattice = linear_equation(3, 5, 60).unwrap();
+        let solutions: Vec<_> = lattice.solutions_in(0..=100, 0..=100).collect();
+        assert_eq!(solutions.len(), 5);
+        assert!(solutions.iter().all(|s| 3 * s.x + 5 * s.y == 60));
+        assert_eq!(lattice.solutions_in(1..=19, 1..=11).count(), 3);
+        assert_eq!(lattice.solutions_in(1..=4, 0..=100).count(), 0);
+
+        let objective = Vector2::new(1, 1);
+        let min = lattice.minimise(0..=100, 0..=100, objective);
+        assert_eq!(min, Some(Vector2::new(0, 12)));
+        let max = lattice.maximise(0..=100, 0..=100, objective);
+        assert_eq!(max, Some(Vector2::new(20, 0)));
+        assert_eq!(lattice.minimise(1..=4, 0..=100, objective), None);
+
+        // A zero step component, 4x + 0y = 4.
+        let lattice = linear_equation(4, 0, 4).unwrap();
+        assert_eq!(lattice.solutions_in(0..=5, 2..=3).count(), 2);
+        assert_eq!(lattice.solutions_in(2..=5, 2..=3).count(), 0);
+    }
+
+    #[test]
+    fn test_linear_system() {
+        let a = vec![vec![2, 4, 6], vec![1, 1, 1]];
+        let (h, u) = hermite_normal_form(&a).unwrap();
+        assert_eq!(h, [[2, 0, 0], [0, 1, 0]]);
+        let det = u[0][0] * (u[1][1] * u[2][2] - u[1][2] * u[2][1])
+            - u[0][1] * (u[1][0] * u[2][2] - u[1][2] * u[2][0])
+            + u[0][2] * (u[1][0] * u[2][1] - u[1][1] * u[2][0]);
+        assert_eq!(det.abs(), 1);
+
+        // 2x + 4y + 6z = 10, x + y + z = 4.
+        let lattice = linear_system(&a, &[10, 4]).unwrap().unwrap();
+        assert_eq!(lattice.basis.len(), 1);
+        for k in -3..3 {
+            let p = lattice.point(&[k]);
+            assert_eq!(2 * p[0] + 4 * p[1] + 6 * p[2], 10);
+            assert_eq!(p[0] + p[1] + p[2], 4);
+        }
+
+        assert_eq!(linear_system(&a, &[11, 4]).unwrap(), None);
+        assert_eq!(linear_system(&[vec![2, 4]], &[3]).unwrap(), None);
+        // Dependent rows have to agree.
+        let dependent = [vec![1, 2], vec![2, 4]];
+        assert!(linear_system(&dependent, &[3, 6]).unwrap().is_some());
+        assert_eq!(linear_system(&dependent, &[3, 7]).unwrap(), None);
+
+        let big = [vec![i64::MAX, 2], vec![3, i64::MAX]];
+        assert!(hermite_normal_form(&big).is_err());
+        assert!(linear_system(&big, &[1, 1]).is_err());
+
+        assert!(linear_system(&a, &[10]).is_err());
+        assert!(linear_system(&[vec![1, 2], vec![1]], &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert_eq!(linear_equation(0, 0, 0), None);
+        assert_eq!(linear_equation(0, 0, 3), None);
+        assert_eq!(linear_equation(3, 5, i64::MAX), None);
+        assert_eq!(linear_equation(i64::MIN, -1, 0), None);
+        // The gcd is -1 here.
+        assert_eq!(linear_equation(-1, 0, i64::MIN), None);
+        let lattice = linear_equation(0, i64::MIN, i64::MIN).unwrap();
+        assert_eq!(lattice.point(5), Vector2::new(5, 1));
+
+        let lattice = Lattice {
+            offset: Vector2::new(1, 1),
+            step: Vector2::zeros(),
+        };
+        assert_eq!(lattice.k_range(0..=2, 0..=2), None);
+        let lattice = linear_equation(1, 1, 0).unwrap();
+        assert_eq!(lattice.k_range(i64::MIN..=0, 0..=0), Some(0..=0));
+        // k goes up to -i64::MIN.
+        let lattice = linear_equation(1, -1, 0).unwrap();
+        assert_eq!(lattice.k_range(i64::MIN..=0, i64::MIN..=0), None);
+    }
 }
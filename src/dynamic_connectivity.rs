@@ -0,0 +1,224 @@
+//! Offline connectivity queries on a graph whose edges come and go.
+//!
+//! Every edge is alive during an interval of the timeline. The intervals are
+//! stored in a segment tree over time, and a depth-first walk of the tree
+//! merges the edges of a node on the way down and rolls them back on the way
+//! up, so every leaf sees exactly the edges alive at its time. That's
+//! O(q log q log n) for q events on n vertices.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::union_find::RollbackUnionFind;
+
+/// One step of the timeline. Edges are undirected and may be added more
+/// than once, a removal removes one copy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    Add(usize, usize),
+    Remove(usize, usize),
+    /// Are the two vertices connected at this point?
+    Connected(usize, usize),
+    /// How many connected components are there at this point?
+    Components,
+}
+
+/// The answer to a query, in the order of the queries.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Answer {
+    Connected(bool),
+    Components(usize),
+}
+
+struct Walk<'a> {
+    events: &'a [Event],
+    /// The edges alive during the whole range of a tree node.
+    tree: Vec<Vec<(usize, usize)>>,
+    uf: RollbackUnionFind,
+    answers: Vec<Answer>,
+}
+
+impl Walk<'_> {
+    /// Add the edge to the nodes covering `start..end`.
+    fn insert(
+        &mut self,
+        node: usize,
+        range: (usize, usize),
+        start: usize,
+        end: usize,
+        edge: (usize, usize),
+    ) {
+        let (lo, hi) = range;
+        if end <= lo || hi <= start {
+            return;
+        }
+        if start <= lo && hi <= end {
+            self.tree[node].push(edge);
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.insert(2 * node, (lo, mid), start, end, edge);
+        self.insert(2 * node + 1, (mid, hi), start, end, edge);
+    }
+
+    fn visit(&mut self, node: usize, (lo, hi): (usize, usize)) {
+        let snapshot = self.uf.snapshot();
+        for i in 0..self.tree[node].len() {
+            let (a, b) = self.tree[node][i];
+            self.uf.union(a, b);
+        }
+        if hi - lo == 1 {
+            match self.events[lo] {
+                Event::Connected(a, b) => {
+                    self.answers.push(Answer::Connected(self.uf.same_set(a, b)))
+                }
+                Event::Components => self.answers.push(Answer::Components(self.uf.nr_sets())),
+                Event::Add(..) | Event::Remove(..) => {}
+            }
+        } else {
+            let mid = (lo + hi) / 2;
+            self.visit(2 * node, (lo, mid));
+            self.visit(2 * node + 1, (mid, hi));
+        }
+        self.uf.rollback(snapshot);
+    }
+}
+
+/// Answer all queries of the timeline on a graph with the vertices `0..n`.
+pub fn offline_connectivity(n: usize, events: &[Event]) -> Result<Vec<Answer>> {
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+
+    // Find the interval during which each copy of an edge is alive.
+    let mut alive: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    let mut intervals = vec![];
+    for (t, &event) in events.iter().enumerate() {
+        match event {
+            Event::Add(a, b) | Event::Remove(a, b) | Event::Connected(a, b) if a.max(b) >= n => {
+                bail!("Vertex out of range at {}: {:?}", t, event)
+            }
+            Event::Add(a, b) => alive.entry(key(a, b)).or_default().push(t),
+            Event::Remove(a, b) => match alive.get_mut(&key(a, b)).and_then(Vec::pop) {
+                Some(start) => intervals.push((start, t, key(a, b))),
+                None => bail!("Removing missing edge {}-{} at {}", a, b, t),
+            },
+            Event::Connected(..) | Event::Components => {}
+        }
+    }
+    for (edge, starts) in alive {
+        intervals.extend(starts.into_iter().map(|start| (start, events.len(), edge)));
+    }
+
+    if events.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut walk = Walk {
+        events,
+        tree: vec![vec![]; 4 * events.len()],
+        uf: RollbackUnionFind::new(n),
+        answers: vec![],
+    };
+    let root = (0, events.len());
+    for (start, end, edge) in intervals {
+        walk.insert(1, root, start, end, edge);
+    }
+    walk.visit(1, root);
+    Ok(walk.answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::union_find::{SetIndex, UnionFind};
+
+    /// Answer the queries by rebuilding the components from scratch.
+    fn brute_force(n: usize, events: &[Event]) -> Vec<Answer> {
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut answers = vec![];
+        for &event in events {
+            let mut uf = UnionFind::with_sets(n);
+            for &(a, b) in &edges {
+                uf.union(SetIndex::from(a), SetIndex::from(b));
+            }
+            match event {
+                Event::Add(a, b) => edges.push((a, b)),
+                Event::Remove(a, b) => {
+                    let i = edges
+                        .iter()
+                        .rposition(|&e| e == (a, b) || e == (b, a))
+                        .unwrap();
+                    edges.remove(i);
+                }
+                Event::Connected(a, b) => answers.push(Answer::Connected(
+                    uf.same_set(SetIndex::from(a), SetIndex::from(b)),
+                )),
+                Event::Components => answers.push(Answer::Components(uf.nr_sets())),
+            }
+        }
+        answers
+    }
+
+    #[test]
+    fn test_small() {
+        use Event::*;
+        let events = [
+            Add(0, 1),
+            Add(1, 2),
+            Connected(0, 2),
+            Components,
+            Remove(2, 1),
+            Connected(0, 2),
+            Add(0, 2),
+            Add(0, 2),
+            Remove(0, 2),
+            Connected(1, 2),
+            Components,
+        ];
+        let answers = offline_connectivity(4, &events).unwrap();
+        assert_eq!(
+            answers,
+            [
+                Answer::Connected(true),
+                Answer::Components(2),
+                Answer::Connected(false),
+                Answer::Connected(true),
+                Answer::Components(2),
+            ]
+        );
+        assert_eq!(answers, brute_force(4, &events));
+
+        assert!(offline_connectivity(4, &[Remove(0, 1)]).is_err());
+        assert!(offline_connectivity(4, &[Add(0, 4)]).is_err());
+        assert_eq!(offline_connectivity(4, &[]).unwrap(), []);
+    }
+
+    #[test]
+    fn test_random() {
+        let n = 8;
+        let mut lcg = crate::testing::lcg(12345);
+        let mut rand = |m: usize| lcg(m as u64) as usize;
+
+        let mut edges = vec![];
+        let mut events = vec![];
+        for _ in 0..300 {
+            let event = match rand(4) {
+                0 | 1 => {
+                    let edge = (rand(n), rand(n));
+                    edges.push(edge);
+                    Event::Add(edge.0, edge.1)
+                }
+                2 if !edges.is_empty() => {
+                    let (a, b) = edges.swap_remove(rand(edges.len()));
+                    Event::Remove(b, a)
+                }
+                _ if rand(2) == 0 => Event::Components,
+                _ => Event::Connected(rand(n), rand(n)),
+            };
+            events.push(event);
+        }
+        assert_eq!(
+            offline_connectivity(n, &events).unwrap(),
+            brute_force(n, &events)
+        );
+    }
+}
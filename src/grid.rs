@@ -0,0 +1,410 @@
+//! Dense 2D grids, as they show up in most AoC inputs.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use anyhow::{bail, Result};
+
+/// A position in a grid, `(x, y)` with `y` pointing downwards.
+pub type Pos = (usize, usize);
+
+/// Offsets of the 4 orthogonal neighbours, the von Neumann neighbourhood.
+pub const NEIGHBOURS_4: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of the 8 surrounding neighbours, the Moore neighbourhood.
+pub const NEIGHBOURS_8: [(i64, i64); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangular grid stored in row-major order.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Constructs a grid from its cells in row-major order.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Result<Grid<T>> {
+        if cells.len() != width * height {
+            bail!(
+                "Expected {} cells for a {}x{} grid, got {}",
+                width * height,
+                width,
+                height,
+                cells.len()
+            );
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Constructs a grid from a list of rows, which all need to have the
+    /// same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                bail!(
+                    "Length of row {} ({}) does not match the length of the first row ({})",
+                    y,
+                    row.len(),
+                    width
+                );
+            }
+            cells.extend(row);
+        }
+        Grid::from_vec(width, height, cells)
+    }
+
+    /// Constructs a grid by calling `f` for every position.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Pos) -> T) -> Grid<T> {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a character map, one row per line. Empty lines are skipped.
+    pub fn parse_with(input: &str, mut f: impl FnMut(u8) -> T) -> Result<Grid<T>> {
+        Grid::try_parse_with(input, |c| Ok(f(c)))
+    }
+
+    /// Like [Grid::parse_with], but the conversion of a cell can fail.
+    pub fn try_parse_with(input: &str, mut f: impl FnMut(u8) -> Result<T>) -> Result<Grid<T>> {
+        let rows = input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.bytes().map(&mut f).collect())
+            .collect::<Result<Vec<Vec<T>>>>()?;
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn idx(&self, (x, y): Pos) -> usize {
+        y * self.width + x
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.cells[self.idx(pos)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            let idx = self.idx(pos);
+            Some(&mut self.cells[idx])
+        } else {
+            None
+        }
+    }
+
+    /// Move from `pos` by the given offset, or `None` if this leaves the grid.
+    pub fn offset(&self, (x, y): Pos, (dx, dy): (i64, i64)) -> Option<Pos> {
+        let x = usize::try_from(x as i64 + dx).ok()?;
+        let y = usize::try_from(y as i64 + dy).ok()?;
+        self.contains((x, y)).then_some((x, y))
+    }
+
+    /// Move from `pos` by the given offset, wrapping around at the edges.
+    /// `None` if the grid is empty, so there is nothing to wrap around to.
+    pub fn offset_wrapping(&self, (x, y): Pos, (dx, dy): (i64, i64)) -> Option<Pos> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        Some((
+            (x as i64 + dx).rem_euclid(self.width as i64) as usize,
+            (y as i64 + dy).rem_euclid(self.height as i64) as usize,
+        ))
+    }
+
+    /// Get a cell by signed coordinates, `None` if it is outside of the grid.
+    pub fn get_signed(&self, x: i64, y: i64) -> Option<&T> {
+        let x = usize::try_from(x).ok()?;
+        let y = usize::try_from(y).ok()?;
+        self.get((x, y))
+    }
+
+    /// Get a cell by signed coordinates, wrapping around at the edges.
+    /// `None` if the grid is empty.
+    pub fn get_wrapping(&self, x: i64, y: i64) -> Option<&T> {
+        self.get(self.offset_wrapping((0, 0), (x, y))?)
+    }
+
+    /// The 4 orthogonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours_4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_4
+            .into_iter()
+            .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// The up to 8 surrounding neighbours of `pos` that are inside the grid.
+    pub fn neighbours_8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_8
+            .into_iter()
+            .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All cells with their positions, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pos, &mut T)> {
+        self.positions().zip(self.cells.iter_mut())
+    }
+
+    /// All cells in row-major order.
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics for a chunk size of 0.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The cells of column `x`, top to bottom. Panics if `x` is out of
+    /// range, like [Grid::row].
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            x < self.width,
+            "column {} out of range for width {}",
+            x,
+            self.width
+        );
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Position of the first cell matching the predicate, in row-major order.
+    pub fn position(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, v)| pred(v)).map(|(pos, _)| pos)
+    }
+
+    /// Positions of all the cells matching the predicate.
+    pub fn positions_where<'a>(
+        &'a self,
+        mut pred: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Pos> + 'a {
+        self.iter()
+            .filter(move |(_, v)| pred(v))
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn count(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.cells.iter().filter(|v| pred(v)).count()
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Render the grid with one character per cell, e.g. for debugging.
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&mut f));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// The grid mirrored along its main diagonal, rows become columns.
+    pub fn transposed(&self) -> Grid<T> {
+        Grid::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first occurrence of `value`, e.g. a start marker.
+    pub fn find(&self, value: &T) -> Option<Pos> {
+        self.position(|v| v == value)
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a character map into a grid of the raw bytes.
+    pub fn parse(input: &str) -> Result<Grid<u8>> {
+        Grid::parse_with(input, |c| c)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        assert!(
+            self.contains(pos),
+            "position {:?} out of bounds for a {}x{} grid",
+            pos,
+            self.width,
+            self.height
+        );
+        &self.cells[self.idx(pos)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        assert!(
+            self.contains(pos),
+            "position {:?} out of bounds for a {}x{} grid",
+            pos,
+            self.width,
+            self.height
+        );
+        let idx = self.idx(pos);
+        &mut self.cells[idx]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Grid<u8> {
+        Grid::parse("S.#\n..#\n").unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = example();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], b'#');
+        assert_eq!(grid.find(&b'S'), Some((0, 0)));
+        assert_eq!(grid.find(&b'E'), None);
+        assert!(Grid::parse("..\n...\n").is_err());
+
+        let bools = Grid::parse_with("#.\n.#\n", |c| c == b'#').unwrap();
+        assert_eq!(bools.count(|b| *b), 2);
+    }
+
+    #[test]
+    fn test_indexing() {
+        let grid = example();
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get_signed(-1, 0), None);
+        assert_eq!(grid.get_signed(2, 0), Some(&b'#'));
+        assert_eq!(grid.get_wrapping(-1, -1), Some(&b'#'));
+        assert_eq!(grid.get_wrapping(3, 2), Some(&b'S'));
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.offset_wrapping((0, 0), (-1, 0)), Some((2, 0)));
+
+        let empty = Grid::new(3, 0, b'.');
+        assert_eq!(empty.offset_wrapping((0, 0), (1, 1)), None);
+        assert_eq!(empty.get_wrapping(0, 0), None);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = example();
+        assert_eq!(
+            grid.neighbours_4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours_8((1, 0)).count(), 5);
+        assert_eq!(grid.neighbours_8((1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = example();
+        assert_eq!(grid.row(1), b"..#");
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), b"##");
+        assert_eq!(grid.columns().count(), 3);
+        assert!(std::panic::catch_unwind(|| grid.column(3).count()).is_err());
+
+        let t = grid.transposed();
+        assert_eq!((t.width(), t.height()), (2, 3));
+        assert_eq!(t.row(2), b"##");
+        assert_eq!(t.transposed(), grid);
+    }
+
+    #[test]
+    fn test_display() {
+        let grid = example();
+        assert_eq!(grid.render(|&c| c as char), "S.#\n..#\n");
+        assert_eq!(grid.map(|&c| u8::from(c == b'#')).to_string(), "001\n001\n");
+    }
+}
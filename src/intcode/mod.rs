@@ -0,0 +1,680 @@
+//! The Intcode computer from the 2019 puzzles.
+//!
+//! A [Machine] runs until it halts or until it needs an input that hasn't
+//! been provided yet, so that several machines can be chained or run in a
+//! feedback loop by moving outputs of one machine to the inputs of another.
+//!
+//! For debugging there's a [disassemble]r, and machines can record a trace
+//! of every executed instruction, see [Machine::enable_trace]. Programs can
+//! also be run on polynomials instead of numbers, see [symbolic].
+
+pub mod symbolic;
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::io::parse_entries;
+
+/// Memory cells a machine may use. Writing beyond this is an error, so a
+/// stray write to a huge address can't allocate without limit.
+pub const MAX_MEMORY: usize = 1 << 24;
+
+/// Why a machine stopped running.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum State {
+    /// The program executed opcode 99.
+    Halted,
+    /// The program wants to read an input but the input queue is empty.
+    /// Running it again after providing an input resumes the program.
+    NeedsInput,
+}
+
+/// How an instruction parameter is interpreted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+/// A single decoded instruction, with the modes of its parameters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Op {
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+}
+
+impl Op {
+    /// Number of parameters following the opcode.
+    pub fn arity(self) -> usize {
+        match self {
+            Op::Add | Op::Mul | Op::LessThan | Op::Equals => 3,
+            Op::JumpIfTrue | Op::JumpIfFalse => 2,
+            Op::Input | Op::Output | Op::AdjustBase => 1,
+            Op::Halt => 0,
+        }
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Mul => "mul",
+            Op::Input => "in",
+            Op::Output => "out",
+            Op::JumpIfTrue => "jnz",
+            Op::JumpIfFalse => "jz",
+            Op::LessThan => "lt",
+            Op::Equals => "eq",
+            Op::AdjustBase => "arb",
+            Op::Halt => "hlt",
+        }
+    }
+}
+
+/// A raw instruction parameter together with its mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Param {
+    pub mode: Mode,
+    pub value: i64,
+}
+
+/// Immediate values are shown as is, addresses in brackets, with `rb` for
+/// the relative base.
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            Mode::Immediate => write!(f, "{}", self.value),
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Relative => write!(f, "[rb{:+}]", self.value),
+        }
+    }
+}
+
+fn write_instruction(f: &mut fmt::Formatter<'_>, op: Op, params: &[Param]) -> fmt::Result {
+    write!(f, "{:<3}", op.mnemonic())?;
+    for (i, param) in params.iter().enumerate() {
+        write!(f, "{}{}", if i == 0 { " " } else { ", " }, param)?;
+    }
+    Ok(())
+}
+
+/// A line of a disassembled program.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Line {
+    Instruction {
+        addr: usize,
+        op: Op,
+        params: Vec<Param>,
+    },
+    /// A value that doesn't decode to an instruction, or an instruction
+    /// whose parameters run past the end of the program.
+    Data { addr: usize, value: i64 },
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Instruction { addr, op, params } => {
+                write!(f, "{:>5}: ", addr)?;
+                write_instruction(f, *op, params)
+            }
+            Line::Data { addr, value } => write!(f, "{:>5}: data {}", addr, value),
+        }
+    }
+}
+
+/// Decode a program from start to end. Since code and data are mixed in
+/// Intcode and programs may modify themselves, this is only a best guess:
+/// everything that decodes to an instruction is shown as one.
+pub fn disassemble(program: &[i64]) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut addr = 0;
+    while addr < program.len() {
+        match decode(program[addr]) {
+            Ok((op, modes)) if addr + op.arity() < program.len() => {
+                let params = (0..op.arity())
+                    .map(|i| Param {
+                        mode: modes[i],
+                        value: program[addr + 1 + i],
+                    })
+                    .collect();
+                lines.push(Line::Instruction { addr, op, params });
+                addr += 1 + op.arity();
+            }
+            _ => {
+                lines.push(Line::Data {
+                    addr,
+                    value: program[addr],
+                });
+                addr += 1;
+            }
+        }
+    }
+    lines
+}
+
+/// The disassembled program as text, one line per instruction.
+pub fn listing(program: &[i64]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Side effects of a traced instruction, in the order they happened.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    Read { addr: usize, value: i64 },
+    Write { addr: usize, value: i64 },
+    Input(i64),
+    Output(i64),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Read { addr, value } => write!(f, "read [{}] = {}", addr, value),
+            Event::Write { addr, value } => write!(f, "write [{}] = {}", addr, value),
+            Event::Input(value) => write!(f, "input {}", value),
+            Event::Output(value) => write!(f, "output {}", value),
+        }
+    }
+}
+
+/// An executed instruction, as recorded by a traced machine.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraceEntry {
+    pub ip: usize,
+    pub op: Op,
+    pub params: Vec<Param>,
+    /// The relative base before the instruction was executed.
+    pub relative_base: i64,
+    pub events: Vec<Event>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>5}: ", self.ip)?;
+        write_instruction(f, self.op, &self.params)?;
+        if self.params.iter().any(|p| p.mode == Mode::Relative) {
+            write!(f, " (rb = {})", self.relative_base)?;
+        }
+        for (i, event) in self.events.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " | " } else { ", " }, event)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split an instruction into its opcode and the modes of its parameters.
+pub fn decode(instruction: i64) -> Result<(Op, [Mode; 3])> {
+    if instruction < 0 {
+        bail!("Negative instruction {}", instruction);
+    }
+    let op = match instruction % 100 {
+        1 => Op::Add,
+        2 => Op::Mul,
+        3 => Op::Input,
+        4 => Op::Output,
+        5 => Op::JumpIfTrue,
+        6 => Op::JumpIfFalse,
+        7 => Op::LessThan,
+        8 => Op::Equals,
+        9 => Op::AdjustBase,
+        99 => Op::Halt,
+        c => bail!("Unknown opcode {} in instruction {}", c, instruction),
+    };
+
+    let mut modes = [Mode::Position; 3];
+    let mut flags = instruction / 100;
+    for mode in &mut modes {
+        *mode = match flags % 10 {
+            0 => Mode::Position,
+            1 => Mode::Immediate,
+            2 => Mode::Relative,
+            m => bail!(
+                "Unknown parameter mode {} in instruction {}",
+                m,
+                instruction
+            ),
+        };
+        flags /= 10;
+    }
+    Ok((op, modes))
+}
+
+/// Parse a comma separated Intcode program.
+pub fn parse_program(input: &str) -> Result<Vec<i64>> {
+    parse_entries(input.trim(), ',')
+}
+
+#[derive(Clone, Debug)]
+pub struct Machine {
+    memory: Vec<i64>,
+    ip: usize,
+    relative_base: i64,
+    inputs: VecDeque<i64>,
+    outputs: VecDeque<i64>,
+    halted: bool,
+    trace: Option<Vec<TraceEntry>>,
+    events: Vec<Event>,
+}
+
+impl Machine {
+    pub fn new(program: Vec<i64>) -> Machine {
+        Machine {
+            memory: program,
+            ip: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+            halted: false,
+            trace: None,
+            events: vec![],
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Machine> {
+        Ok(Machine::new(parse_program(input)?))
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Read from memory, addresses beyond the program are zero.
+    pub fn read(&self, addr: usize) -> i64 {
+        self.memory.get(addr).copied().unwrap_or(0)
+    }
+
+    /// Write to memory, growing it if the address is beyond the program.
+    /// Fails for addresses beyond [MAX_MEMORY].
+    pub fn write(&mut self, addr: usize, value: i64) -> Result<()> {
+        if addr >= self.memory.len() {
+            if addr >= MAX_MEMORY {
+                bail!(
+                    "Address {} at {} is beyond the memory limit of {} cells",
+                    addr,
+                    self.ip,
+                    MAX_MEMORY
+                );
+            }
+            self.memory.resize(addr + 1, 0);
+        }
+        self.memory[addr] = value;
+        Ok(())
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn extend_input(&mut self, values: impl IntoIterator<Item = i64>) {
+        self.inputs.extend(values);
+    }
+
+    pub fn pop_output(&mut self) -> Option<i64> {
+        self.outputs.pop_front()
+    }
+
+    /// Remove and return all outputs produced so far.
+    pub fn take_outputs(&mut self) -> Vec<i64> {
+        self.outputs.drain(..).collect()
+    }
+
+    /// Record every instruction executed from now on. The trace grows
+    /// without bounds, so this is meant for debugging small runs.
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    /// The instructions executed since the trace was enabled.
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    /// Remove and return the trace recorded so far, tracing continues.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// The trace as text, one line per executed instruction.
+    pub fn trace_text(&self) -> String {
+        self.trace()
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect()
+    }
+
+    fn log(&mut self, event: Event) {
+        if self.trace.is_some() {
+            self.events.push(event);
+        }
+    }
+
+    fn address(&self, param: usize, mode: Mode) -> Result<usize> {
+        let value = self.read(self.ip + 1 + param);
+        let addr = match mode {
+            Mode::Position => value,
+            Mode::Relative => self
+                .relative_base
+                .checked_add(value)
+                .ok_or_else(|| anyhow!("Relative address overflows at {}", self.ip))?,
+            Mode::Immediate => bail!("Cannot write to an immediate parameter at {}", self.ip),
+        };
+        usize::try_from(addr).map_err(|_| anyhow!("Negative address {} at {}", addr, self.ip))
+    }
+
+    fn param(&mut self, param: usize, mode: Mode) -> Result<i64> {
+        if mode == Mode::Immediate {
+            return Ok(self.read(self.ip + 1 + param));
+        }
+        let addr = self.address(param, mode)?;
+        let value = self.read(addr);
+        self.log(Event::Read { addr, value });
+        Ok(value)
+    }
+
+    fn store(&mut self, param: usize, mode: Mode, value: i64) -> Result<()> {
+        let addr = self.address(param, mode)?;
+        self.log(Event::Write { addr, value });
+        self.write(addr, value)
+    }
+
+    fn record(&mut self, op: Op, modes: [Mode; 3], relative_base: i64) {
+        if let Some(trace) = &mut self.trace {
+            let params = (0..op.arity())
+                .map(|i| Param {
+                    mode: modes[i],
+                    value: self.memory.get(self.ip + 1 + i).copied().unwrap_or(0),
+                })
+                .collect();
+            trace.push(TraceEntry {
+                ip: self.ip,
+                op,
+                params,
+                relative_base,
+                events: std::mem::take(&mut self.events),
+            });
+        }
+    }
+
+    /// Execute a single instruction. Returns `Some` if the machine can't
+    /// continue, in which case the instruction pointer is left unchanged.
+    pub fn step(&mut self) -> Result<Option<State>> {
+        if self.halted {
+            return Ok(Some(State::Halted));
+        }
+
+        self.events.clear();
+        let relative_base = self.relative_base;
+        let (op, modes) = decode(self.read(self.ip))?;
+        let mut next = self.ip + 1 + op.arity();
+        match op {
+            Op::Add | Op::Mul | Op::LessThan | Op::Equals => {
+                let a = self.param(0, modes[0])?;
+                let b = self.param(1, modes[1])?;
+                let value = match op {
+                    Op::Add => a.checked_add(b),
+                    Op::Mul => a.checked_mul(b),
+                    Op::LessThan => Some((a < b) as i64),
+                    _ => Some((a == b) as i64),
+                }
+                .ok_or_else(|| anyhow!("{:?} of {} and {} overflows at {}", op, a, b, self.ip))?;
+                self.store(2, modes[2], value)?;
+            }
+            Op::Input => {
+                let value = match self.inputs.pop_front() {
+                    Some(value) => value,
+                    None => return Ok(Some(State::NeedsInput)),
+                };
+                self.log(Event::Input(value));
+                self.store(0, modes[0], value)?;
+            }
+            Op::Output => {
+                let value = self.param(0, modes[0])?;
+                self.log(Event::Output(value));
+                self.outputs.push_back(value);
+            }
+            Op::JumpIfTrue | Op::JumpIfFalse => {
+                let condition = self.param(0, modes[0])? != 0;
+                if condition == (op == Op::JumpIfTrue) {
+                    let target = self.param(1, modes[1])?;
+                    next = usize::try_from(target)
+                        .map_err(|_| anyhow!("Jump to negative address {}", target))?;
+                }
+            }
+            Op::AdjustBase => {
+                let offset = self.param(0, modes[0])?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or_else(|| anyhow!("Relative base overflows at {}", self.ip))?;
+            }
+            Op::Halt => {
+                self.halted = true;
+                self.record(op, modes, relative_base);
+                return Ok(Some(State::Halted));
+            }
+        }
+        self.record(op, modes, relative_base);
+        self.ip = next;
+        Ok(None)
+    }
+
+    /// Run until the program halts or needs more input.
+    pub fn run(&mut self) -> Result<State> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Run a program to completion on the given inputs and return all of
+    /// its outputs.
+    pub fn run_with(&mut self, inputs: impl IntoIterator<Item = i64>) -> Result<Vec<i64>> {
+        self.extend_input(inputs);
+        match self.run()? {
+            State::Halted => Ok(self.take_outputs()),
+            State::NeedsInput => bail!("Program needs more input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str, inputs: &[i64]) -> Vec<i64> {
+        Machine::parse(program)
+            .unwrap()
+            .run_with(inputs.iter().copied())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_day_2() {
+        let mut machine = Machine::parse("1,9,10,3,2,3,11,0,99,30,40,50").unwrap();
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(
+            machine.memory(),
+            [3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+    }
+
+    #[test]
+    fn test_comparisons_and_jumps() {
+        // Compares the input to 8, in the different addressing modes.
+        for program in ["3,9,8,9,10,9,4,9,99,-1,8", "3,3,1108,-1,8,3,4,3,99"] {
+            assert_eq!(run(program, &[8]), [1]);
+            assert_eq!(run(program, &[7]), [0]);
+        }
+
+        let larger = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+                      1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+                      999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        assert_eq!(run(larger, &[7]), [999]);
+        assert_eq!(run(larger, &[8]), [1000]);
+        assert_eq!(run(larger, &[9]), [1001]);
+    }
+
+    #[test]
+    fn test_relative_base_and_growing_memory() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let expected: Vec<i64> = parse_program(quine).unwrap();
+        assert_eq!(run(quine, &[]), expected);
+
+        assert_eq!(run("104,1125899906842624,99", &[]), [1125899906842624]);
+        assert_eq!(
+            run("1102,34915192,34915192,7,4,7,99,0", &[]),
+            [1219070632396864]
+        );
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        // Amplifier chain from 2019 day 7, each machine gets its phase first.
+        let program = parse_program(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,\
+             27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        )
+        .unwrap();
+        let mut machines: Vec<_> = [9, 8, 7, 6, 5]
+            .into_iter()
+            .map(|phase| {
+                let mut machine = Machine::new(program.clone());
+                machine.push_input(phase);
+                machine
+            })
+            .collect();
+
+        let mut signal = 0;
+        while !machines[4].is_halted() {
+            for machine in &mut machines {
+                machine.push_input(signal);
+                machine.run().unwrap();
+                signal = machine.pop_output().unwrap();
+            }
+        }
+        assert_eq!(signal, 139629729);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = parse_program("1,9,10,3,2,3,11,0,99,30,40,50").unwrap();
+        assert_eq!(
+            disassemble(&program)[0],
+            Line::Instruction {
+                addr: 0,
+                op: Op::Add,
+                params: vec![
+                    Param {
+                        mode: Mode::Position,
+                        value: 9
+                    },
+                    Param {
+                        mode: Mode::Position,
+                        value: 10
+                    },
+                    Param {
+                        mode: Mode::Position,
+                        value: 3
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            listing(&program).lines().collect::<Vec<_>>(),
+            [
+                "    0: add [9], [10], [3]",
+                "    4: mul [3], [11], [0]",
+                "    8: hlt",
+                "    9: data 30",
+                "   10: data 40",
+                "   11: data 50",
+            ]
+        );
+        assert_eq!(
+            listing(&[109, -1, 21101, 2, 3, 4, 4]),
+            "    0: arb -1\n    2: add 2, 3, [rb+4]\n    6: data 4\n"
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut machine = Machine::parse("3,9,1001,9,5,10,4,10,99,0,0").unwrap();
+        machine.enable_trace();
+        assert_eq!(machine.run_with([7]).unwrap(), [12]);
+
+        let trace = machine.trace();
+        assert_eq!(trace.len(), 4);
+        assert_eq!(
+            trace[0].events,
+            [Event::Input(7), Event::Write { addr: 9, value: 7 }]
+        );
+        assert_eq!(
+            trace[1].events,
+            [
+                Event::Read { addr: 9, value: 7 },
+                Event::Write {
+                    addr: 10,
+                    value: 12
+                }
+            ]
+        );
+        assert_eq!(trace[3].op, Op::Halt);
+        assert_eq!(
+            machine.trace_text().lines().nth(1),
+            Some("    2: add [9], 5, [10] | read [9] = 7, write [10] = 12")
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Machine::parse("42").unwrap().run().is_err());
+        assert!(Machine::parse("1101,1,1,-1,99").unwrap().run().is_err());
+        let mut machine = Machine::parse("3,0,99").unwrap();
+        assert_eq!(machine.run().unwrap(), State::NeedsInput);
+        assert!(machine.run_with([]).is_err());
+
+        let overflow = format!("1101,{},1,0,99", i64::MAX);
+        let err = Machine::parse(&overflow).unwrap().run().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Add of {} and 1 overflows at 0", i64::MAX)
+        );
+        let overflow = format!("109,{},109,1,99", i64::MAX);
+        assert!(Machine::parse(&overflow).unwrap().run().is_err());
+        let overflow = format!("109,1,22201,{},0,0,99", i64::MAX);
+        assert!(Machine::parse(&overflow).unwrap().run().is_err());
+
+        let huge = format!("1101,1,1,{},99", MAX_MEMORY);
+        let err = Machine::parse(&huge).unwrap().run().unwrap_err();
+        assert!(err.to_string().contains("memory limit"));
+        let mut machine = Machine::parse("99").unwrap();
+        assert!(machine.write(usize::MAX, 1).is_err());
+    }
+}
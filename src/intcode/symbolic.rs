@@ -0,0 +1,380 @@
+//! Symbolic execution of Intcode programs.
+//!
+//! Memory cells hold polynomials instead of numbers, so a program can be run
+//! with some cells set to variables, e.g. the noun and verb of 2019 day 2.
+//! Additions and multiplications just build up bigger polynomials. Reading
+//! through an address that depends on the variables gives an unknown value,
+//! which is fine as long as it is overwritten before it is used. Anything
+//! that needs an actual number, like an address, a jump condition or an
+//! opcode, has to evaluate to a constant, otherwise the run stops with
+//! [Outcome::NeedsConcrete] and the caller has to fall back to concrete runs.
+//! The same happens if a coefficient overflows.
+
+use anyhow::{anyhow, bail, Result};
+
+use super::{decode, Mode, Op, MAX_MEMORY};
+use crate::bivariate_polynomial::BP;
+
+/// How a symbolic run ended.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Halted,
+    /// The instruction at `ip` needs a value that depends on the symbolic
+    /// inputs, `reason` says which one.
+    NeedsConcrete {
+        ip: usize,
+        reason: String,
+    },
+}
+
+/// The contents of a memory cell.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Cell {
+    Known(BP),
+    /// Computed from a value that the instruction at `ip` read through an
+    /// address depending on the symbolic inputs.
+    Unknown {
+        ip: usize,
+    },
+}
+
+impl Cell {
+    fn describe(&self) -> String {
+        match self {
+            Cell::Known(value) => value.to_string(),
+            Cell::Unknown { ip } => format!("a value read at {} through a symbolic address", ip),
+        }
+    }
+}
+
+/// Why an instruction couldn't be executed.
+enum Stop {
+    NeedsConcrete(String),
+    Failed(anyhow::Error),
+}
+
+impl From<anyhow::Error> for Stop {
+    fn from(e: anyhow::Error) -> Stop {
+        Stop::Failed(e)
+    }
+}
+
+type Step<T> = std::result::Result<T, Stop>;
+
+#[derive(Clone, Debug)]
+pub struct SymbolicMachine {
+    memory: Vec<Cell>,
+    ip: usize,
+    relative_base: i64,
+    outputs: Vec<Cell>,
+}
+
+impl SymbolicMachine {
+    pub fn new(program: &[i64]) -> SymbolicMachine {
+        let memory = program
+            .iter()
+            .map(|&v| Cell::Known(BP::constant(v)))
+            .collect();
+        SymbolicMachine {
+            memory,
+            ip: 0,
+            relative_base: 0,
+            outputs: vec![],
+        }
+    }
+
+    pub fn memory(&self) -> &[Cell] {
+        &self.memory
+    }
+
+    pub fn outputs(&self) -> &[Cell] {
+        &self.outputs
+    }
+
+    pub fn cell(&self, addr: usize) -> Cell {
+        self.memory
+            .get(addr)
+            .cloned()
+            .unwrap_or_else(|| Cell::Known(BP::constant(0)))
+    }
+
+    /// The polynomial in a cell, `None` if it is unknown.
+    pub fn get(&self, addr: usize) -> Option<BP> {
+        match self.cell(addr) {
+            Cell::Known(value) => Some(value),
+            Cell::Unknown { .. } => None,
+        }
+    }
+
+    /// Set a cell, e.g. to make it a symbolic input. Memory grows as needed,
+    /// up to [MAX_MEMORY] cells like for [super::Machine::write].
+    pub fn set(&mut self, addr: usize, value: BP) -> Result<()> {
+        self.store(addr, Cell::Known(value))
+    }
+
+    fn store(&mut self, addr: usize, cell: Cell) -> Result<()> {
+        if addr >= self.memory.len() {
+            if addr >= MAX_MEMORY {
+                bail!(
+                    "Address {} at {} is beyond the memory limit of {} cells",
+                    addr,
+                    self.ip,
+                    MAX_MEMORY
+                );
+            }
+            self.memory.resize(addr + 1, Cell::Known(BP::constant(0)));
+        }
+        self.memory[addr] = cell;
+        Ok(())
+    }
+
+    fn constant(&self, cell: &Cell, what: &str) -> Step<i64> {
+        match cell {
+            Cell::Known(value) => value.get_constant(),
+            Cell::Unknown { .. } => None,
+        }
+        .ok_or_else(|| {
+            Stop::NeedsConcrete(format!(
+                "{} depends on the symbolic inputs: {}",
+                what,
+                cell.describe()
+            ))
+        })
+    }
+
+    /// The address a parameter refers to, `None` if it depends on the
+    /// symbolic inputs.
+    fn address(&self, param: usize, mode: Mode) -> Step<Option<usize>> {
+        let value = match self.cell(self.ip + 1 + param) {
+            Cell::Known(value) => match value.get_constant() {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+            Cell::Unknown { .. } => return Ok(None),
+        };
+        let addr = match mode {
+            Mode::Position => value,
+            Mode::Relative => self
+                .relative_base
+                .checked_add(value)
+                .ok_or_else(|| anyhow!("Relative address overflows at {}", self.ip))?,
+            Mode::Immediate => {
+                return Err(anyhow!("Cannot write to an immediate parameter at {}", self.ip).into())
+            }
+        };
+        match usize::try_from(addr) {
+            Ok(addr) => Ok(Some(addr)),
+            Err(_) => Err(anyhow!("Negative address {} at {}", addr, self.ip).into()),
+        }
+    }
+
+    fn param(&self, param: usize, mode: Mode) -> Step<Cell> {
+        if mode == Mode::Immediate {
+            return Ok(self.cell(self.ip + 1 + param));
+        }
+        Ok(match self.address(param, mode)? {
+            Some(addr) => self.cell(addr),
+            None => Cell::Unknown { ip: self.ip },
+        })
+    }
+
+    fn concrete_param(&self, param: usize, mode: Mode) -> Step<i64> {
+        self.constant(
+            &self.param(param, mode)?,
+            &format!("parameter {}", param + 1),
+        )
+    }
+
+    fn dest(&self, param: usize, mode: Mode) -> Step<usize> {
+        self.address(param, mode)?.ok_or_else(|| {
+            Stop::NeedsConcrete(format!(
+                "the address of parameter {} depends on the symbolic inputs",
+                param + 1
+            ))
+        })
+    }
+
+    /// Execute a single instruction. Returns `None` if the machine can
+    /// continue.
+    fn step(&mut self) -> Step<Option<Outcome>> {
+        let instruction = self.constant(&self.cell(self.ip), "the instruction")?;
+        let (op, modes) = decode(instruction)?;
+        let mut next = self.ip + 1 + op.arity();
+
+        match op {
+            Op::Add | Op::Mul => {
+                let result = match (self.param(0, modes[0])?, self.param(1, modes[1])?) {
+                    (Cell::Known(a), Cell::Known(b)) => {
+                        let result = if op == Op::Add {
+                            a.checked_add(&b)
+                        } else {
+                            a.checked_mul(&b)
+                        };
+                        Cell::Known(result.ok_or_else(|| {
+                            Stop::NeedsConcrete(format!(
+                                "the coefficients of {:?} of {} and {} overflow",
+                                op, a, b
+                            ))
+                        })?)
+                    }
+                    (unknown @ Cell::Unknown { .. }, _) | (_, unknown) => unknown,
+                };
+                let dest = self.dest(2, modes[2])?;
+                self.store(dest, result)?;
+            }
+            Op::LessThan | Op::Equals => {
+                let a = self.concrete_param(0, modes[0])?;
+                let b = self.concrete_param(1, modes[1])?;
+                let dest = self.dest(2, modes[2])?;
+                let result = if op == Op::LessThan { a < b } else { a == b };
+                self.set(dest, BP::constant(result as i64))?;
+            }
+            Op::JumpIfTrue | Op::JumpIfFalse => {
+                let condition = self.concrete_param(0, modes[0])? != 0;
+                if condition == (op == Op::JumpIfTrue) {
+                    let target = self.concrete_param(1, modes[1])?;
+                    next = usize::try_from(target)
+                        .map_err(|_| anyhow!("Jump to negative address {}", target))?;
+                }
+            }
+            Op::Input => return Err(Stop::NeedsConcrete("inputs are not supported".to_owned())),
+            Op::Output => {
+                let value = self.param(0, modes[0])?;
+                self.outputs.push(value);
+            }
+            Op::AdjustBase => {
+                let offset = self.concrete_param(0, modes[0])?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or_else(|| anyhow!("Relative base overflows at {}", self.ip))?;
+            }
+            Op::Halt => return Ok(Some(Outcome::Halted)),
+        }
+        self.ip = next;
+        Ok(None)
+    }
+
+    /// Run until the program halts or needs a concrete value. Errors are
+    /// reserved for programs that would fail in a concrete run as well.
+    pub fn run(&mut self) -> Result<Outcome> {
+        loop {
+            match self.step() {
+                Ok(None) => {}
+                Ok(Some(outcome)) => return Ok(outcome),
+                Err(Stop::NeedsConcrete(reason)) => {
+                    return Ok(Outcome::NeedsConcrete {
+                        ip: self.ip,
+                        reason,
+                    })
+                }
+                Err(Stop::Failed(e)) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse_program;
+
+    #[test]
+    fn test_polynomial_result() {
+        // [0] = ([9] + [10]) * [10]
+        let program = parse_program("1,9,10,0,2,0,10,0,99,0,0").unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(9, BP::x()).unwrap();
+        machine.set(10, BP::y()).unwrap();
+        assert_eq!(machine.run().unwrap(), Outcome::Halted);
+        assert_eq!(machine.get(0), Some(BP::x() * BP::y() + BP::y() * BP::y()));
+    }
+
+    #[test]
+    fn test_symbolic_addresses() {
+        // Reads through the symbolic cells 1 and 2 into [3], which is then
+        // overwritten with [9] + [9].
+        let program = parse_program("1,0,0,3,1,9,9,3,99,0").unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(1, BP::x()).unwrap();
+        machine.set(2, BP::y()).unwrap();
+        machine.set(9, BP::y()).unwrap();
+        assert_eq!(machine.run().unwrap(), Outcome::Halted);
+        assert_eq!(machine.get(3), Some(BP::y() + BP::y()));
+
+        // Without the overwrite, the result is unknown.
+        let program = parse_program("1,0,0,3,99").unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(1, BP::x()).unwrap();
+        assert_eq!(machine.run().unwrap(), Outcome::Halted);
+        assert_eq!(machine.cell(3), Cell::Unknown { ip: 0 });
+
+        // Writing through a symbolic address can't be tracked.
+        let program = parse_program("1,0,0,0,99").unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(3, BP::x()).unwrap();
+        match machine.run().unwrap() {
+            Outcome::NeedsConcrete { ip, reason } => {
+                assert_eq!(ip, 0);
+                assert!(reason.contains("parameter 3"), "{}", reason);
+            }
+            outcome => panic!("Unexpected outcome {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn test_jumps() {
+        // Jumps are fine as long as the condition is known.
+        let program = parse_program("1105,1,4,99,1,9,9,0,99,0").unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(9, BP::y()).unwrap();
+        assert_eq!(machine.run().unwrap(), Outcome::Halted);
+        assert_eq!(machine.get(0), Some(BP::y() + BP::y()));
+
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(1, BP::x()).unwrap();
+        assert!(matches!(
+            machine.run().unwrap(),
+            Outcome::NeedsConcrete { ip: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // [0] = [5] * [5], where the coefficient of x² overflows.
+        let program = parse_program("2,5,5,0,99").unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        machine.set(5, BP::x().scale(&(1 << 32))).unwrap();
+        match machine.run().unwrap() {
+            Outcome::NeedsConcrete { ip, reason } => {
+                assert_eq!(ip, 0);
+                assert!(reason.contains("overflow"), "{}", reason);
+            }
+            outcome => panic!("Unexpected outcome {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        // Writing beyond the memory limit fails like in a concrete run.
+        let program = parse_program(&format!("1101,1,1,{},99", MAX_MEMORY)).unwrap();
+        let mut machine = SymbolicMachine::new(&program);
+        let error = machine.run().unwrap_err().to_string();
+        assert!(error.contains("memory limit"), "{}", error);
+        assert!(machine.set(MAX_MEMORY, BP::x()).is_err());
+
+        // Overflowing relative addresses and bases are errors as well.
+        let program = parse_program(&format!("109,{},22201,1,1,1,99", i64::MAX)).unwrap();
+        let error = SymbolicMachine::new(&program)
+            .run()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Relative address overflows"), "{}", error);
+        let program = parse_program(&format!("109,{},109,1,99", i64::MAX)).unwrap();
+        let error = SymbolicMachine::new(&program)
+            .run()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Relative base overflows"), "{}", error);
+    }
+}
@@ -0,0 +1,264 @@
+//! Interpolation and extrapolation of polynomial sequences.
+//!
+//! Many puzzles ask for a value after an enormous number of steps, where the
+//! values turn out to be a polynomial in the step number. A polynomial of
+//! degree `d` is determined by `d + 1` samples, any further samples can be
+//! used to check that the sequence really is polynomial, see [fit].
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::polynomial::{Field, Polynomial, Ring};
+
+/// Newton's forward differences of samples taken at `0, 1, 2, ...`, i.e.
+/// `[y₀, Δy₀, Δ²y₀, ...]`.
+pub fn differences<C: Ring>(samples: &[C]) -> Result<Vec<C>> {
+    let mut row = samples.to_vec();
+    let mut out = Vec::with_capacity(samples.len());
+    while let Some(first) = row.first() {
+        out.push(first.clone());
+        row = row
+            .windows(2)
+            .map(|w| w[1].checked_sub(&w[0]))
+            .collect::<Option<_>>()
+            .ok_or_else(|| anyhow!("Overflow computing the differences"))?;
+    }
+    Ok(out)
+}
+
+/// Evaluate Newton's forward difference formula
+/// `p(n) = Σ Δᵏy₀ · binomial(n, k)` at `n`, which may be negative.
+fn newton_forward<C: Ring>(differences: &[C], n: i64) -> Result<C> {
+    let overflow = || anyhow!("Overflow extrapolating to {}", n);
+    let mut out = C::zero();
+    let mut binomial: i128 = 1;
+    for (k, d) in differences.iter().enumerate() {
+        if k > 0 {
+            // The product of k consecutive integers is divisible by k!, so
+            // the division is exact even for negative n.
+            binomial = binomial
+                .checked_mul(n as i128 - k as i128 + 1)
+                .ok_or_else(overflow)?
+                / k as i128;
+        }
+        let term = C::from_i128(binomial)
+            .and_then(|b| d.checked_mul(&b))
+            .ok_or_else(overflow)?;
+        out = out.checked_add(&term).ok_or_else(overflow)?;
+    }
+    Ok(out)
+}
+
+/// A sample that disagrees with the polynomial through the samples before
+/// it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mismatch<C> {
+    pub index: usize,
+    pub expected: C,
+    pub actual: C,
+}
+
+/// How well a sequence fits a polynomial of a given degree.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Fit<C> {
+    pub degree: usize,
+    /// Number of samples from the start that lie on the polynomial through
+    /// the first `degree + 1` samples.
+    pub agreeing: usize,
+    /// The first sample that doesn't.
+    pub mismatch: Option<Mismatch<C>>,
+}
+
+impl<C> Fit<C> {
+    /// Whether all samples agree, and there were more than the polynomial
+    /// needs, so the fit was actually checked.
+    pub fn is_confirmed(&self) -> bool {
+        self.mismatch.is_none() && self.agreeing > self.degree + 1
+    }
+}
+
+/// Check whether samples taken at `0, 1, 2, ...` fit a polynomial of at most
+/// the given degree.
+pub fn fit<C: Ring>(samples: &[C], degree: usize) -> Result<Fit<C>> {
+    let basis = &samples[..samples.len().min(degree + 1)];
+    let differences = differences(basis)?;
+    for (index, actual) in samples.iter().enumerate().skip(basis.len()) {
+        let expected = newton_forward(&differences, index as i64)?;
+        if expected != *actual {
+            return Ok(Fit {
+                degree,
+                agreeing: index,
+                mismatch: Some(Mismatch {
+                    index,
+                    expected,
+                    actual: actual.clone(),
+                }),
+            });
+        }
+    }
+    Ok(Fit {
+        degree,
+        agreeing: samples.len(),
+        mismatch: None,
+    })
+}
+
+/// The value at step `n` of a sequence sampled at steps `0, 1, 2, ...`,
+/// assuming it is a polynomial of the given degree. Samples beyond the
+/// first `degree + 1` are checked to fit.
+pub fn extrapolate<C: Ring>(samples: &[C], degree: usize, n: i64) -> Result<C> {
+    if samples.len() <= degree {
+        bail!(
+            "{} samples are not enough for a polynomial of degree {}",
+            samples.len(),
+            degree
+        );
+    }
+    let fit = fit(samples, degree)?;
+    if let Some(m) = fit.mismatch {
+        bail!(
+            "Sample {} is {}, but a polynomial of degree {} predicts {}",
+            m.index,
+            m.actual,
+            degree,
+            m.expected
+        );
+    }
+    newton_forward(&differences(&samples[..=degree])?, n)
+}
+
+fn check_distinct<F: Field>(points: &[(F, F)]) -> Result<()> {
+    for (i, (a, _)) in points.iter().enumerate() {
+        if points[..i].iter().any(|(b, _)| a == b) {
+            bail!("Duplicate sample at {}", a);
+        }
+    }
+    Ok(())
+}
+
+/// The polynomial in `var` of lowest degree through the given points, using
+/// Lagrange's formula.
+pub fn lagrange<F: Field>(points: &[(F, F)], var: &str) -> Result<Polynomial<F>> {
+    check_distinct(points)?;
+    let x = Polynomial::var(var);
+    let mut out = Polynomial::zero();
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut basis = Polynomial::constant(F::one());
+        let mut denominator = F::one();
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                basis = x
+                    .checked_sub(&Polynomial::constant(xj.clone()))
+                    .and_then(|factor| basis.checked_mul(&factor))
+                    .ok_or_else(|| anyhow!("Overflow interpolating at {}", xi))?;
+                denominator = xi
+                    .checked_sub(xj)
+                    .and_then(|d| denominator.checked_mul(&d))
+                    .ok_or_else(|| anyhow!("Overflow interpolating at {}", xi))?;
+            }
+        }
+        let scale = yi
+            .checked_div(&denominator)
+            .ok_or_else(|| anyhow!("Overflow interpolating at {}", xi))?;
+        out = basis
+            .checked_scale(&scale)
+            .and_then(|term| out.checked_add(&term))
+            .ok_or_else(|| anyhow!("Overflow interpolating at {}", xi))?;
+    }
+    Ok(out)
+}
+
+/// The same polynomial as [lagrange], using Newton's divided differences.
+pub fn newton<F: Field>(points: &[(F, F)], var: &str) -> Result<Polynomial<F>> {
+    check_distinct(points)?;
+    let overflow = || anyhow!("Overflow computing divided differences");
+
+    // After round k, coeffs[i] holds the divided difference f[x_i-k, ..., x_i].
+    let mut coeffs: Vec<F> = points.iter().map(|(_, y)| y.clone()).collect();
+    for k in 1..points.len() {
+        for i in (k..points.len()).rev() {
+            let dy = coeffs[i].checked_sub(&coeffs[i - 1]).ok_or_else(overflow)?;
+            let dx = points[i]
+                .0
+                .checked_sub(&points[i - k].0)
+                .ok_or_else(overflow)?;
+            coeffs[i] = dy.checked_div(&dx).ok_or_else(overflow)?;
+        }
+    }
+
+    // Horner's scheme on c₀ + (x - x₀)(c₁ + (x - x₁)(c₂ + ...)).
+    let x = Polynomial::var(var);
+    let mut out = Polynomial::zero();
+    for (c, (xi, _)) in coeffs.iter().zip(points).rev() {
+        out = x
+            .checked_sub(&Polynomial::constant(xi.clone()))
+            .and_then(|factor| out.checked_mul(&factor))
+            .and_then(|out| out.checked_add(&Polynomial::constant(c.clone())))
+            .ok_or_else(|| anyhow!("Overflow expanding the Newton polynomial"))?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_rational::Ratio;
+
+    #[test]
+    fn test_extrapolate() {
+        assert_eq!(differences(&[1, 4, 9, 16]).unwrap(), [1, 3, 2, 0]);
+
+        let samples = [10, 13, 16, 21, 30, 45];
+        assert_eq!(extrapolate(&samples, 3, 6).unwrap(), 68);
+        assert_eq!(extrapolate(&samples, 3, -1).unwrap(), 5);
+        assert!(extrapolate(&samples, 2, 6).is_err());
+        assert!(extrapolate(&samples[..3], 3, 6).is_err());
+
+        // Large n, with the intermediate binomials fitting into an i128.
+        let n = 1_000_000_000;
+        assert_eq!(
+            extrapolate(&[0i128, 1, 4], 2, n).unwrap(),
+            (n as i128).pow(2)
+        );
+    }
+
+    #[test]
+    fn test_fit() {
+        let squares = [0, 1, 4, 9, 16, 26];
+        assert_eq!(
+            fit(&squares, 2).unwrap(),
+            Fit {
+                degree: 2,
+                agreeing: 5,
+                mismatch: Some(Mismatch {
+                    index: 5,
+                    expected: 25,
+                    actual: 26
+                })
+            }
+        );
+        assert!(fit(&squares[..5], 2).unwrap().is_confirmed());
+        assert!(!fit(&squares[..3], 2).unwrap().is_confirmed());
+        assert_eq!(fit(&squares, 1).unwrap().agreeing, 2);
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let r = |n: i64| Ratio::from_integer(n);
+        let points = [(r(0), r(1)), (r(2), r(2)), (r(-1), r(4)), (r(3), r(-2))];
+        let p = lagrange(&points, "x").unwrap();
+        assert_eq!(newton(&points, "x").unwrap(), p);
+        for (x, y) in &points {
+            assert_eq!(p.evaluate(&[("x", *x)]).unwrap(), *y);
+        }
+
+        let line = newton(&[(r(0), r(0)), (r(2), r(1))], "n").unwrap();
+        assert_eq!(line, Polynomial::term(Ratio::new(1, 2), &[("n", 1)]));
+
+        assert!(lagrange(&[(r(1), r(1)), (r(1), r(2))], "x").is_err());
+
+        // The products of the (x - xᵢ) overflow.
+        let far = [(r(0), r(0)), (r(1 << 40), r(0)), (r(3 << 40), r(1))];
+        assert!(lagrange(&far, "x").is_err());
+        assert!(newton(&far, "x").is_err());
+    }
+}
@@ -1,11 +1,27 @@
 #![warn(clippy::all)]
 
+pub mod answers;
+pub mod automaton;
 pub mod bivariate_polynomial;
 pub mod diophantine;
+pub mod dynamic_connectivity;
+pub mod grid;
+pub mod intcode;
+pub mod interpolation;
 pub mod io;
+pub mod modular;
+pub mod number_theory;
+pub mod ocr;
 pub mod octtree;
 pub mod parse;
+pub mod polynomial;
+pub mod report;
+pub mod solution;
+pub mod sparse_grid;
+#[cfg(test)]
+mod testing;
 pub mod union_find;
+pub mod visualise;
 
 pub mod y2019;
 pub mod y2020;
@@ -13,40 +29,3 @@ pub mod y2021;
 pub mod y2022;
 pub mod y2023;
 pub mod y2025;
-
-#[macro_export]
-macro_rules! test_task {
-    ($year:ident, $day:ident, $suffix:ident, ($sol_a:expr, _)) => {
-        paste::paste! {
-            #[test]
-            fn [<test_ $day _ $suffix>]() -> anyhow::Result<()> {
-                let path = format!("data/{}/{}_{}.txt", stringify!($year), stringify!($day), stringify!($suffix));
-                let sol = crate::$year::$day::solve(&crate::io::file_str(path)?)?;
-                assert_eq!(sol.0, $sol_a);
-                Ok(())
-            }
-        }
-    };
-    ($year:ident, $day:ident, $suffix:ident, (_, $sol_b:expr)) => {
-        paste::paste! {
-            #[test]
-            fn [<test_ $day _ $suffix>]() -> anyhow::Result<()> {
-                let path = format!("data/{}/{}_{}.txt", stringify!($year), stringify!($day), stringify!($suffix));
-                let sol = crate::$year::$day::solve(&crate::io::file_str(path)?)?;
-                assert_eq!(sol.1, $sol_b);
-                Ok(())
-            }
-        }
-    };
-    ($year:ident, $day:ident, $suffix:ident, $solution:expr) => {
-        paste::paste! {
-            #[test]
-            fn [<test_ $day _ $suffix>]() -> anyhow::Result<()> {
-                let path = format!("data/{}/{}_{}.txt", stringify!($year), stringify!($day), stringify!($suffix));
-                let sol = crate::$year::$day::solve(&crate::io::file_str(path)?)?;
-                assert_eq!(sol, $solution);
-                Ok(())
-            }
-        }
-    };
-}
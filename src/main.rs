@@ -1,8 +1,206 @@
-use advent::{io::file_str, y2021};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-fn main() {
-    println!(
-        "{:?}",
-        y2021::d22::solve(&file_str("data/y2021/d22_full.txt").unwrap()).unwrap()
-    );
+use advent::io::file_str;
+use advent::report::{self, CountingAllocator, Measurement};
+use advent::solution::{self, Entry};
+use advent::visualise::{self, Files, Terminal};
+use anyhow::{anyhow, bail, Context, Result};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+const USAGE: &str =
+    "Usage: advent <all | YEAR [DAY]> [--input SUFFIX] [--report] [--json FILE] [--csv FILE]
+              [--visualise] [--frames DIR]
+
+Runs the solutions on data/y{YEAR}/d{DAY}_{SUFFIX}.txt, SUFFIX defaults to 'full'.
+If no file with exactly this suffix exists, all files starting with
+d{DAY}_{SUFFIX}_ are used instead, e.g. '--input example' picks up
+d12_example_1.txt, d12_example_2.txt and so on.
+
+With --report, the runtime of every part and the peak memory usage of every
+day is measured and printed as a table, slowest days first. Days that solve
+both parts in one go only get a total. --json and --csv
+additionally write the measurements to the given file and imply --report.
+
+Days that support it can show their intermediate states: --visualise animates
+them in the terminal, --frames writes every frame to a text file in DIR.";
+
+#[derive(Debug, Default)]
+struct Options {
+    suffix: String,
+    report: bool,
+    json: Option<PathBuf>,
+    csv: Option<PathBuf>,
+}
+
+/// Find the input files for a day, following the same
+/// `data/{year}/{day}_{suffix}.txt` convention as the answer manifests.
+fn input_files(year: u32, day: u32, suffix: &str) -> Result<Vec<PathBuf>> {
+    let dir = PathBuf::from(format!("data/y{}", year));
+    let exact = dir.join(format!("d{:02}_{}.txt", day, suffix));
+    if exact.is_file() {
+        return Ok(vec![exact]);
+    }
+
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let prefix = format!("d{:02}_{}_", day, suffix);
+    let mut files = vec![];
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("Cannot list {:?}", dir))? {
+        let path = entry?.path();
+        let matches = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".txt"));
+        if matches {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+fn run_file(entry: &Entry, path: &Path, options: &Options) -> Result<Option<Measurement>> {
+    let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("?");
+    let suffix = name.split_once('_').map_or(name, |(_, suffix)| suffix);
+    println!("{} day {:02} ({})", entry.year, entry.day, suffix);
+
+    let input = file_str(path)?;
+    let (answers, measurement) = if options.report {
+        let measurement = report::measure(entry, suffix, &input, &ALLOCATOR)?;
+        (measurement.answers.clone(), Some(measurement))
+    } else {
+        (entry.solve(&input)?, None)
+    };
+
+    if let Some(part1) = answers.part1 {
+        println!("    Part 1: {}", part1);
+    }
+    if let Some(part2) = answers.part2 {
+        println!("    Part 2: {}", part2);
+    }
+    Ok(measurement)
+}
+
+fn write_report(mut measurements: Vec<Measurement>, options: &Options) -> Result<()> {
+    report::sort_slowest_first(&mut measurements);
+    println!("\n{}", report::table(&measurements));
+
+    if let Some(path) = &options.json {
+        std::fs::write(path, report::json(&measurements))
+            .with_context(|| format!("Cannot write {:?}", path))?;
+    }
+    if let Some(path) = &options.csv {
+        std::fs::write(path, report::csv(&measurements))
+            .with_context(|| format!("Cannot write {:?}", path))?;
+    }
+    Ok(())
+}
+
+/// Run the selected days. Missing input files are an error when a single day
+/// is requested, and are skipped when running a whole year.
+fn run(year: Option<u32>, day: Option<u32>, options: &Options) -> Result<()> {
+    let selected: Vec<_> = solution::all()
+        .filter(|e| year.is_none_or(|year| year == e.year) && day.is_none_or(|day| day == e.day))
+        .collect();
+
+    if selected.is_empty() {
+        match (year, day) {
+            (Some(year), Some(day)) => bail!("No solution for {} day {:02}", year, day),
+            (Some(year), None) => bail!("No solutions for {}", year),
+            _ => bail!("No solutions available"),
+        }
+    }
+
+    let single = day.is_some();
+    let mut measurements = vec![];
+    for entry in selected {
+        let (year, day) = (entry.year, entry.day);
+        let files = input_files(year, day, &options.suffix)?;
+        if files.is_empty() {
+            if single {
+                bail!(
+                    "No '{}' input found for {} day {:02}",
+                    options.suffix,
+                    year,
+                    day
+                );
+            }
+            continue;
+        }
+
+        for path in files {
+            let measurement = run_file(entry, &path, options)
+                .with_context(|| format!("Failed to solve {} day {:02}", year, day))?;
+            measurements.extend(measurement);
+        }
+    }
+
+    if options.report {
+        write_report(measurements, options)?;
+    }
+
+    Ok(())
+}
+
+fn parse_number(arg: &str, what: &str) -> Result<u32> {
+    arg.parse()
+        .map_err(|_| anyhow!("Invalid {} '{}'\n\n{}", what, arg, USAGE))
+}
+
+fn main() -> Result<()> {
+    let mut positional = vec![];
+    let mut options = Options {
+        suffix: "full".to_owned(),
+        ..Default::default()
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value for {}\n\n{}", arg, USAGE))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "-i" | "--input" => options.suffix = value()?,
+            "--report" => options.report = true,
+            "--json" => {
+                options.json = Some(value()?.into());
+                options.report = true;
+            }
+            "--csv" => {
+                options.csv = Some(value()?.into());
+                options.report = true;
+            }
+            "--visualise" => visualise::set_sink(Some(Box::new(Terminal {
+                delay: Duration::from_millis(50),
+            }))),
+            "--frames" => {
+                let dir = value()?;
+                let files = Files::new(&dir).with_context(|| format!("Cannot create {}", dir))?;
+                visualise::set_sink(Some(Box::new(files)));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    match &positional[..] {
+        [all] if all == "all" => run(None, None, &options),
+        [year] => run(Some(parse_number(year, "year")?), None, &options),
+        [year, day] => run(
+            Some(parse_number(year, "year")?),
+            Some(parse_number(day, "day")?),
+            &options,
+        ),
+        _ => bail!("{}", USAGE),
+    }
 }
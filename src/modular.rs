@@ -0,0 +1,76 @@
+//! Integers modulo a constant `M`.
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// An integer in `0..M`. `M` has to fit into an `i64`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Modular<const M: u64>(u64);
+
+impl<const M: u64> Modular<M> {
+    pub fn new(n: i64) -> Self {
+        Modular((n as i128).rem_euclid(M as i128) as u64)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const M: u64> Add for Modular<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Modular(((self.0 as u128 + other.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Sub for Modular<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<const M: u64> Mul for Modular<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Modular(((self.0 as u128 * other.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Neg for Modular<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Modular((M - self.0) % M)
+    }
+}
+
+impl<const M: u64> fmt::Display for Modular<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type M7 = Modular<7>;
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(M7::new(-1).value(), 6);
+        assert_eq!(M7::new(15).value(), 1);
+        assert_eq!(M7::new(5) + M7::new(4), M7::new(2));
+        assert_eq!(M7::new(2) - M7::new(4), M7::new(5));
+        assert_eq!(M7::new(3) * M7::new(5), M7::new(1));
+        assert_eq!(-M7::new(0), M7::new(0));
+
+        let big = Modular::<{ i64::MAX as u64 }>::new(-2);
+        assert_eq!((big * big).value(), 4);
+    }
+}
@@ -0,0 +1,362 @@
+//! Modular arithmetic, primes and friends.
+//!
+//! Values are `i64` with `i128` intermediates, so products of residues don't
+//! overflow. Residues returned by the modular functions are in `0..m`.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+
+/// `None` if the gcd is 2⁶³, i.e. for `gcd(i64::MIN, 0)` and
+/// `gcd(i64::MIN, i64::MIN)`.
+pub fn gcd(a: i64, b: i64) -> Option<i64> {
+    if b == 0 {
+        a.checked_abs()
+    } else {
+        // Unlike `%`, this doesn't panic for i64::MIN % -1.
+        gcd(b, a.wrapping_rem(b))
+    }
+}
+
+/// `None` if the lcm doesn't fit into an `i64`.
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)?).checked_mul(b)?.checked_abs()
+}
+
+/// The extended Euclidean algorithm, returns `(g, x, y)` with
+/// `a * x + b * y = g`, where `g` is the gcd of `a` and `b` up to its sign.
+/// `None` if a coefficient overflows, which can only happen with `i64::MIN`.
+pub fn egcd(a: i64, b: i64) -> Option<(i64, i64, i64)> {
+    if a == 0 {
+        Some((b, 0, 1))
+    } else {
+        let (g, x, y) = egcd(b.wrapping_rem(a), a)?;
+        Some((g, y.checked_sub(b.checked_div(a)?.checked_mul(x)?)?, x))
+    }
+}
+
+fn mul_i64(a: i64, b: i64, m: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(m as i128) as i64
+}
+
+fn pow_i64(base: i64, mut exp: u64, m: i64) -> i64 {
+    let mut base = base.rem_euclid(m);
+    let mut out = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            out = mul_i64(out, base, m);
+        }
+        base = mul_i64(base, base, m);
+        exp >>= 1;
+    }
+    out
+}
+
+/// `None` if `m` isn't positive.
+pub fn mod_mul(a: i64, b: i64, m: i64) -> Option<i64> {
+    (m > 0).then(|| mul_i64(a, b, m))
+}
+
+/// `None` if `m` isn't positive.
+pub fn mod_pow(base: i64, exp: u64, m: i64) -> Option<i64> {
+    (m > 0).then(|| pow_i64(base, exp, m))
+}
+
+/// The `x` with `a * x ≡ 1 (mod m)`, if `m` is positive and `a` and `m` are
+/// coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = egcd(a.rem_euclid(m), m)?;
+    (g.abs() == 1).then(|| x.rem_euclid(m))
+}
+
+/// The solutions of a system of congruences `x ≡ r (mod m)` are again of
+/// that form, with `m` the lcm of the moduli.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Congruence {
+    pub residue: i64,
+    pub modulus: i64,
+}
+
+/// Solve a system of congruences with the Chinese remainder theorem. The
+/// moduli don't need to be coprime, `None` if the congruences contradict
+/// each other. Fails if a modulus isn't positive or the lcm of the moduli
+/// doesn't fit into an `i64`.
+pub fn crt(congruences: &[Congruence]) -> Result<Option<Congruence>> {
+    let mut out = Congruence {
+        residue: 0,
+        modulus: 1,
+    };
+    for c in congruences {
+        if c.modulus <= 0 {
+            bail!("CRT modulus {} is not positive", c.modulus);
+        }
+        let (r1, m1) = (out.residue as i128, out.modulus as i128);
+        let (r2, m2) = (c.residue as i128, c.modulus as i128);
+        // Both moduli are positive, so their gcd is too.
+        let g = gcd(out.modulus, c.modulus).unwrap() as i128;
+        if (r2 - r1) % g != 0 {
+            return Ok(None);
+        }
+        // x = r1 + m1 * k, where m1 * k ≡ r2 - r1 (mod m2).
+        let m2g = m2 / g;
+        let Some(inverse) = mod_inverse((m1 / g % m2g) as i64, m2g as i64) else {
+            return Ok(None);
+        };
+        let k = ((r2 - r1) / g).rem_euclid(m2g) * inverse as i128 % m2g;
+        let modulus = m1 * m2g;
+        out = Congruence {
+            residue: (r1 + m1 * k).rem_euclid(modulus) as i64,
+            modulus: i64::try_from(modulus).context("CRT modulus overflows an i64")?,
+        };
+    }
+    Ok(Some(out))
+}
+
+/// The smallest `x ≥ 0` with `base^x ≡ target (mod m)`, using baby-step
+/// giant-step in O(√m). `None` if there is none, and unless `m` is positive
+/// and coprime to `base`.
+pub fn discrete_log(base: i64, target: i64, m: i64) -> Option<u64> {
+    let factor = mod_inverse(base, m)?;
+    let n = isqrt(m as u64) + 1;
+    let target = target.rem_euclid(m);
+
+    // Baby steps, base^j for j < n, keeping the smallest j for each value.
+    let mut baby = HashMap::with_capacity(n as usize);
+    let mut value = 1 % m;
+    for j in 0..n {
+        baby.entry(value).or_insert(j);
+        value = mul_i64(value, base, m);
+    }
+
+    // Giant steps, target * base^(-n * i).
+    let factor = pow_i64(factor, n, m);
+    let mut gamma = target;
+    for i in 0..n {
+        if let Some(j) = baby.get(&gamma) {
+            return Some(i * n + j);
+        }
+        gamma = mul_i64(gamma, factor, m);
+    }
+    None
+}
+
+/// The largest `r` with `r * r ≤ n`.
+pub fn isqrt(n: u64) -> u64 {
+    // The float estimate is off by at most one, in either direction.
+    let mut r = (n as f64).sqrt() as u64;
+    while r.checked_mul(r).is_none_or(|sq| sq > n) {
+        r -= 1;
+    }
+    while (r + 1).checked_mul(r + 1).is_some_and(|sq| sq <= n) {
+        r += 1;
+    }
+    r
+}
+
+fn mul_u64(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_u64(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut out = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            out = mul_u64(out, base, m);
+        }
+        base = mul_u64(base, base, m);
+        exp >>= 1;
+    }
+    out
+}
+
+/// Deterministic Miller-Rabin, these bases cover all 64 bit integers.
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&p) = BASES.iter().find(|&&p| n.is_multiple_of(p)) {
+        return n == p;
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow_u64(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_u64(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+fn gcd_u64(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd_u64(b, a % b)
+    }
+}
+
+/// Find a non-trivial divisor of a composite `n` with Pollard's rho.
+fn pollard_rho(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    for c in 1.. {
+        let f = |x: u64| ((x as u128 * x as u128 + c) % n as u128) as u64;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd_u64(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!()
+}
+
+/// The prime factors of `n` with their multiplicities, in ascending order.
+pub fn factorise(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = vec![];
+    let mut stack = vec![n];
+    while let Some(n) = stack.pop() {
+        if n <= 1 {
+            continue;
+        }
+        if is_prime(n) {
+            primes.push(n);
+            continue;
+        }
+        let d = pollard_rho(n);
+        stack.push(d);
+        stack.push(n / d);
+    }
+    primes.sort_unstable();
+
+    let mut out: Vec<(u64, u32)> = vec![];
+    for p in primes {
+        match out.last_mut() {
+            Some((q, k)) if *q == p => *k += 1,
+            _ => out.push((p, 1)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(gcd(-12, 18), Some(6));
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(mod_pow(3, 200, 13), Some(9));
+        assert_eq!(mod_pow(-2, 3, 5), Some(2));
+        assert_eq!(mod_pow(5, 0, 1), Some(0));
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(-3, 7), Some(2));
+        assert_eq!(mod_inverse(4, 6), None);
+
+        let big = i64::MAX - 24;
+        assert_eq!(mod_mul(big - 1, big - 1, big), Some(1));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(gcd(i64::MIN, 0), None);
+        assert_eq!(gcd(i64::MIN, i64::MIN), None);
+        assert_eq!(gcd(i64::MIN, 6), Some(2));
+        assert_eq!(lcm(i64::MAX, 2), None);
+        assert_eq!(gcd(i64::MIN, -1), Some(1));
+        assert_eq!(egcd(i64::MIN, 1), Some((1, 0, 1)));
+        assert_eq!(egcd(i64::MIN, -1), None);
+        assert_eq!(egcd(i64::MIN, 3).map(|(g, _, _)| g.abs()), Some(1));
+
+        for m in [0, -7] {
+            assert_eq!(mod_mul(2, 3, m), None);
+            assert_eq!(mod_pow(2, 3, m), None);
+            assert_eq!(mod_inverse(2, m), None);
+            assert_eq!(discrete_log(2, 1, m), None);
+            let c = Congruence {
+                residue: 1,
+                modulus: m,
+            };
+            assert!(crt(&[c]).is_err());
+        }
+
+        let big = |residue| Congruence {
+            residue,
+            modulus: i64::MAX,
+        };
+        let other = Congruence {
+            residue: 0,
+            modulus: 2,
+        };
+        assert!(crt(&[big(1), other]).is_err());
+    }
+
+    #[test]
+    fn test_crt() {
+        let c = |residue, modulus| Congruence { residue, modulus };
+        assert_eq!(crt(&[c(2, 3), c(3, 5), c(2, 7)]).unwrap(), Some(c(23, 105)));
+        // Non-coprime moduli.
+        assert_eq!(crt(&[c(2, 4), c(4, 6)]).unwrap(), Some(c(10, 12)));
+        assert_eq!(crt(&[c(1, 4), c(2, 6)]).unwrap(), None);
+        assert_eq!(crt(&[c(-1, 5)]).unwrap(), Some(c(4, 5)));
+        assert_eq!(crt(&[]).unwrap(), Some(c(0, 1)));
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+        assert_eq!(discrete_log(2, 1, 11), Some(0));
+        // 2 only generates the quadratic residues mod 7.
+        assert_eq!(discrete_log(2, 3, 7), None);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(isqrt(999_999_999_999_999_999), 999_999_999);
+    }
+
+    #[test]
+    fn test_primes() {
+        let primes: Vec<u64> = (0..30).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(is_prime(20201227));
+        assert!(!is_prime(3215031751)); // A strong pseudoprime to bases 2 to 7.
+        assert!(is_prime(u64::MAX - 58));
+
+        assert_eq!(factorise(1), []);
+        assert_eq!(factorise(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            factorise(600851475143),
+            [(71, 1), (839, 1), (1471, 1), (6857, 1)]
+        );
+        assert_eq!(
+            factorise(4294967291 * 4294967279),
+            [(4294967279, 1), (4294967291, 1)]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Recognition of the block letters that some days draw as their answer.
+//!
+//! AoC uses two fonts, one with letters 6 pixels high and one with letters
+//! 10 pixels high, with at least one empty column between letters. Letters
+//! are compared after removing empty columns on either side, so the varying
+//! glyph widths don't matter.
+
+use anyhow::{bail, Result};
+
+use crate::grid::Grid;
+use crate::sparse_grid::SparseGrid;
+
+const FONT_6: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const FONT_10: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+/// Crop a set of points to their bounding box.
+pub fn crop(points: impl IntoIterator<Item = (i64, i64)>) -> Grid<bool> {
+    let mut sparse = SparseGrid::new(false);
+    for pos in points {
+        sparse.set(pos, true);
+    }
+    match sparse.bounds() {
+        Some(bounds) => sparse.to_grid(bounds),
+        None => Grid::new(0, 0, false),
+    }
+}
+
+/// Render columns `xs` of the grid in the same format as the fonts.
+fn glyph(grid: &Grid<bool>, xs: std::ops::Range<usize>) -> String {
+    grid.rows()
+        .map(|row| {
+            row[xs.clone()]
+                .iter()
+                .map(|&b| if b { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Remove the empty columns on either side of a glyph.
+fn trim(glyph: &str) -> String {
+    let rows: Vec<&str> = glyph.lines().collect();
+    let filled = |x: usize| rows.iter().any(|row| row.as_bytes()[x] == b'#');
+    let width = rows.first().map_or(0, |row| row.len());
+    let start = (0..width).find(|&x| filled(x)).unwrap_or(width);
+    let end = (start..width)
+        .rfind(|&x| filled(x))
+        .map_or(start, |x| x + 1);
+    rows.iter()
+        .map(|row| &row[start..end])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read the letters drawn with `true` cells. The grid should be cropped to
+/// the letters, see [crop].
+pub fn recognise(grid: &Grid<bool>) -> Result<String> {
+    let font = match grid.height() {
+        6 => FONT_6,
+        10 => FONT_10,
+        h => bail!("No font with letters {} pixels high", h),
+    };
+
+    let empty: Vec<bool> = grid.columns().map(|mut c| c.all(|&b| !b)).collect();
+    let mut out = String::new();
+    let mut x = 0;
+    while x < grid.width() {
+        if empty[x] {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < grid.width() && !empty[x] {
+            x += 1;
+        }
+
+        let glyph = glyph(grid, start..x);
+        match font.iter().find(|(_, pattern)| trim(pattern) == glyph) {
+            Some((letter, _)) => out.push(*letter),
+            None => bail!("Unknown letter at x = {}:\n{}", start, glyph),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(picture: &str) -> Grid<bool> {
+        Grid::parse_with(picture, |c| c == b'#').unwrap()
+    }
+
+    #[test]
+    fn test_small_font() {
+        let picture = "\
+            .##..###...##.\n\
+            #..#.#..#.#..#\n\
+            #..#.###..#...\n\
+            ####.#..#.#...\n\
+            #..#.#..#.#..#\n\
+            #..#.###...##.\n";
+        assert_eq!(recognise(&parse(picture)).unwrap(), "ABC");
+    }
+
+    #[test]
+    fn test_large_font() {
+        let picture = "\
+            #....#..######\n\
+            #....#.......#\n\
+            .#..#........#\n\
+            .#..#.......#.\n\
+            ..##.......#..\n\
+            ..##......#...\n\
+            .#..#....#....\n\
+            .#..#...#.....\n\
+            #....#..#.....\n\
+            #....#..######\n";
+        assert_eq!(recognise(&parse(picture)).unwrap(), "XZ");
+    }
+
+    #[test]
+    fn test_crop_and_errors() {
+        // An "I" drawn somewhere off the origin.
+        let points = [(10, 5), (11, 5), (12, 5), (12, 10), (11, 10), (10, 10)]
+            .into_iter()
+            .chain((6..10).map(|y| (11, y)));
+        assert_eq!(recognise(&crop(points)).unwrap(), "I");
+
+        assert!(recognise(&parse("#####\n#...#\n#####\n")).is_err());
+        assert!(recognise(&parse("####\n#..#\n#..#\n#..#\n#..#\n####\n")).is_err());
+    }
+}
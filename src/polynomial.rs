@@ -0,0 +1,710 @@
+//! Multivariate polynomials with named variables.
+//!
+//! Coefficients can be anything implementing [Ring]. Like integers, the
+//! operators panic if a coefficient overflows, the `checked_` methods
+//! return `None` instead. Evaluation reports overflows as errors.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use anyhow::{anyhow, bail, Result};
+use num_rational::Ratio;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+
+use crate::modular::Modular;
+
+/// The operations polynomials need from their coefficients.
+pub trait Ring: Clone + PartialEq + fmt::Debug + fmt::Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    /// The image of an integer, `None` if it doesn't fit.
+    fn from_i128(n: i128) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// Only used for printing `x - 1` instead of `x + -1`.
+    fn is_negative(&self) -> bool {
+        false
+    }
+}
+
+/// A ring with division, needed e.g. for interpolation.
+pub trait Field: Ring {
+    /// `None` if the divisor is zero or the result overflows.
+    fn checked_div(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! impl_ring_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl Ring for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *other)
+                }
+
+                fn checked_sub(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_sub(*self, *other)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_mul(*self, *other)
+                }
+
+                fn from_i128(n: i128) -> Option<Self> {
+                    <$t>::try_from(n).ok()
+                }
+
+                fn is_negative(&self) -> bool {
+                    *self < 0
+                }
+            }
+        )*
+    };
+}
+
+impl_ring_for_int!(i32, i64, i128);
+
+macro_rules! impl_field_for_ratio {
+    ($($t:ty),*) => {
+        $(
+            impl Ring for Ratio<$t> {
+                fn zero() -> Self {
+                    Ratio::from_integer(0)
+                }
+
+                fn one() -> Self {
+                    Ratio::from_integer(1)
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    CheckedAdd::checked_add(self, other)
+                }
+
+                fn checked_sub(&self, other: &Self) -> Option<Self> {
+                    CheckedSub::checked_sub(self, other)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    CheckedMul::checked_mul(self, other)
+                }
+
+                fn from_i128(n: i128) -> Option<Self> {
+                    <$t>::try_from(n).ok().map(Ratio::from_integer)
+                }
+
+                fn is_negative(&self) -> bool {
+                    *self.numer() < 0
+                }
+            }
+
+            impl Field for Ratio<$t> {
+                fn checked_div(&self, other: &Self) -> Option<Self> {
+                    CheckedDiv::checked_div(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_field_for_ratio!(i64, i128);
+
+impl<const M: u64> Ring for Modular<M> {
+    fn zero() -> Self {
+        Modular::new(0)
+    }
+
+    fn one() -> Self {
+        Modular::new(1)
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(*self + *other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(*self - *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(*self * *other)
+    }
+
+    fn from_i128(n: i128) -> Option<Self> {
+        Some(Modular::new(n.rem_euclid(M as i128) as i64))
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Ring for num_bigint::BigInt {
+    fn zero() -> Self {
+        0.into()
+    }
+
+    fn one() -> Self {
+        1.into()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn from_i128(n: i128) -> Option<Self> {
+        Some(n.into())
+    }
+
+    fn is_negative(&self) -> bool {
+        self.sign() == num_bigint::Sign::Minus
+    }
+}
+
+fn overflow() -> ! {
+    panic!("Polynomial coefficient overflow")
+}
+
+/// A product of variables raised to positive powers, e.g. `x²y`. The empty
+/// product is the monomial of the constant term.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Monomial(BTreeMap<String, u32>);
+
+impl Monomial {
+    /// Zero powers are dropped, repeated variables multiply.
+    pub fn new(powers: &[(&str, u32)]) -> Monomial {
+        let mut out = BTreeMap::new();
+        for &(var, power) in powers.iter().filter(|(_, p)| *p > 0) {
+            *out.entry(var.to_owned()).or_insert(0) += power;
+        }
+        Monomial(out)
+    }
+
+    pub fn power(&self, var: &str) -> u32 {
+        self.0.get(var).copied().unwrap_or(0)
+    }
+
+    /// The total degree, i.e. the sum of all powers.
+    pub fn degree(&self) -> u32 {
+        self.0.values().sum()
+    }
+
+    pub fn powers(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.0.iter().map(|(var, &power)| (var.as_str(), power))
+    }
+
+    fn mul(&self, other: &Monomial) -> Monomial {
+        let mut out = self.clone();
+        for (var, power) in &other.0 {
+            *out.0.entry(var.clone()).or_insert(0) += power;
+        }
+        out
+    }
+
+    /// Split off a variable, returning the rest and the power of `var`.
+    fn split(&self, var: &str) -> (Monomial, u32) {
+        let mut rest = self.clone();
+        let power = rest.0.remove(var).unwrap_or(0);
+        (rest, power)
+    }
+
+    /// Graded lexicographic order, i.e. `x² + xy + y² + x + 1`.
+    fn display_order(&self) -> (Reverse<u32>, Vec<(&str, Reverse<u32>)>) {
+        let powers = self.powers().map(|(v, p)| (v, Reverse(p))).collect();
+        (Reverse(self.degree()), powers)
+    }
+}
+
+const SUPERSCRIPT: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+fn superscript_number(num: u32) -> String {
+    num.to_string()
+        .chars()
+        .map(|c| SUPERSCRIPT[c.to_digit(10).unwrap() as usize])
+        .collect()
+}
+
+impl fmt::Display for Monomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Single letter variables are simply juxtaposed.
+        let sep = if self.0.keys().all(|v| v.chars().count() == 1) {
+            ""
+        } else {
+            "·"
+        };
+        for (i, (var, power)) in self.powers().enumerate() {
+            if i > 0 {
+                write!(f, "{}", sep)?;
+            }
+            write!(f, "{}", var)?;
+            if power > 1 {
+                write!(f, "{}", superscript_number(power))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A polynomial in any number of named variables. Zero coefficients are
+/// never stored, so equal polynomials compare equal.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Polynomial<C> {
+    terms: BTreeMap<Monomial, C>,
+}
+
+impl<C: Ring> Polynomial<C> {
+    pub fn zero() -> Self {
+        Polynomial {
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn constant(c: C) -> Self {
+        Self::term(c, &[])
+    }
+
+    pub fn var(name: &str) -> Self {
+        Self::term(C::one(), &[(name, 1)])
+    }
+
+    /// A single term, e.g. `term(3, &[("x", 2), ("y", 1)])` for `3x²y`.
+    pub fn term(c: C, powers: &[(&str, u32)]) -> Self {
+        Self::from_terms([(Monomial::new(powers), c)])
+    }
+
+    /// Sum up the given terms, the monomials don't have to be distinct.
+    pub fn from_terms(terms: impl IntoIterator<Item = (Monomial, C)>) -> Self {
+        Self::checked_from_terms(terms).unwrap_or_else(|| overflow())
+    }
+
+    /// Like [Self::from_terms], `None` if summing up the coefficients
+    /// overflows.
+    pub fn checked_from_terms(terms: impl IntoIterator<Item = (Monomial, C)>) -> Option<Self> {
+        let mut out = Self::zero();
+        for (monomial, c) in terms {
+            out.add_term(monomial, &c)?;
+        }
+        Some(out)
+    }
+
+    fn add_term(&mut self, monomial: Monomial, c: &C) -> Option<()> {
+        if c.is_zero() {
+            return Some(());
+        }
+        let sum = match self.terms.get(&monomial) {
+            Some(existing) => existing.checked_add(c)?,
+            None => c.clone(),
+        };
+        if sum.is_zero() {
+            self.terms.remove(&monomial);
+        } else {
+            self.terms.insert(monomial, sum);
+        }
+        Some(())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The value of a constant polynomial, `None` if it depends on any
+    /// variable.
+    pub fn get_constant(&self) -> Option<C> {
+        match self.terms.iter().next() {
+            None => Some(C::zero()),
+            Some((m, c)) if self.terms.len() == 1 && m.degree() == 0 => Some(c.clone()),
+            Some(_) => None,
+        }
+    }
+
+    pub fn coeff(&self, monomial: &Monomial) -> C {
+        self.terms.get(monomial).cloned().unwrap_or_else(C::zero)
+    }
+
+    /// The non-zero terms, in no particular order.
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, &C)> {
+        self.terms.iter()
+    }
+
+    /// The variables appearing with a non-zero coefficient.
+    pub fn variables(&self) -> BTreeSet<&str> {
+        self.terms
+            .keys()
+            .flat_map(|m| m.powers().map(|(var, _)| var))
+            .collect()
+    }
+
+    /// The total degree. The zero polynomial has degree 0 as well.
+    pub fn degree(&self) -> u32 {
+        self.terms.keys().map(Monomial::degree).max().unwrap_or(0)
+    }
+
+    /// The highest power of `var`.
+    pub fn degree_in(&self, var: &str) -> u32 {
+        self.terms.keys().map(|m| m.power(var)).max().unwrap_or(0)
+    }
+
+    // Also returns true for constants.
+    pub fn is_linear(&self) -> bool {
+        self.degree() <= 1
+    }
+
+    /// Whether the polynomial is linear in `var` with the other variables
+    /// treated as constants, e.g. `xy + y²` is linear in `x` but not in `y`.
+    pub fn is_linear_in(&self, var: &str) -> bool {
+        self.degree_in(var) <= 1
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut out = self.clone();
+        for (m, c) in &other.terms {
+            out.add_term(m.clone(), c)?;
+        }
+        Some(out)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mut out = Self::zero();
+        for (ma, ca) in &self.terms {
+            for (mb, cb) in &other.terms {
+                out.add_term(ma.mul(mb), &ca.checked_mul(cb)?)?;
+            }
+        }
+        Some(out)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(m, c)| Some((m.clone(), C::zero().checked_sub(c)?)));
+        Self::checked_from_terms(terms.collect::<Option<Vec<_>>>()?)
+    }
+
+    pub fn scale(&self, c: &C) -> Self {
+        self.checked_scale(c).unwrap_or_else(|| overflow())
+    }
+
+    pub fn checked_scale(&self, c: &C) -> Option<Self> {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(m, v)| Some((m.clone(), v.checked_mul(c)?)));
+        Self::checked_from_terms(terms.collect::<Option<Vec<_>>>()?)
+    }
+
+    pub fn pow(&self, n: u32) -> Self {
+        self.checked_pow(n).unwrap_or_else(|| overflow())
+    }
+
+    pub fn checked_pow(&self, mut n: u32) -> Option<Self> {
+        let mut out = Self::constant(C::one());
+        let mut base = self.clone();
+        while n > 0 {
+            if n % 2 == 1 {
+                out = out.checked_mul(&base)?;
+            }
+            n /= 2;
+            if n > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+        Some(out)
+    }
+
+    /// Replace every occurrence of `var` with another polynomial.
+    pub fn substitute(&self, var: &str, value: &Self) -> Self {
+        self.checked_substitute(var, value)
+            .unwrap_or_else(|| overflow())
+    }
+
+    pub fn checked_substitute(&self, var: &str, value: &Self) -> Option<Self> {
+        let mut powers = vec![Self::constant(C::one())];
+        let mut out = Self::zero();
+        for (m, c) in &self.terms {
+            let (rest, power) = m.split(var);
+            while powers.len() <= power as usize {
+                let next = powers.last().unwrap().checked_mul(value)?;
+                powers.push(next);
+            }
+            let rest = Self::checked_from_terms([(rest, c.clone())])?;
+            out = out.checked_add(&powers[power as usize].checked_mul(&rest)?)?;
+        }
+        Some(out)
+    }
+
+    /// Plug in values for some of the variables, the others stay symbolic.
+    pub fn evaluate_partial(&self, values: &[(&str, C)]) -> Result<Self> {
+        let mut out = Self::zero();
+        for (m, c) in &self.terms {
+            let mut rest = m.clone();
+            let mut c = c.clone();
+            for (var, value) in values {
+                let power;
+                (rest, power) = rest.split(var);
+                for _ in 0..power {
+                    c = c.checked_mul(value).ok_or_else(|| {
+                        anyhow!("Overflow evaluating {} at {} = {}", self, var, value)
+                    })?;
+                }
+            }
+            out.add_term(rest, &c)
+                .ok_or_else(|| anyhow!("Overflow evaluating {}", self))?;
+        }
+        Ok(out)
+    }
+
+    /// Evaluate the polynomial, which must not contain any variable missing
+    /// from `values`.
+    pub fn evaluate(&self, values: &[(&str, C)]) -> Result<C> {
+        let rest = self.evaluate_partial(values)?;
+        match rest.get_constant() {
+            Some(c) => Ok(c),
+            None => bail!("No values given for {:?} in {}", rest.variables(), self),
+        }
+    }
+}
+
+impl<'a, C: Ring> Add<&'a Polynomial<C>> for &'a Polynomial<C> {
+    type Output = Polynomial<C>;
+
+    fn add(self, other: &Polynomial<C>) -> Polynomial<C> {
+        self.checked_add(other).unwrap_or_else(|| overflow())
+    }
+}
+
+impl<'a, C: Ring> Sub<&'a Polynomial<C>> for &'a Polynomial<C> {
+    type Output = Polynomial<C>;
+
+    fn sub(self, other: &Polynomial<C>) -> Polynomial<C> {
+        self.checked_sub(other).unwrap_or_else(|| overflow())
+    }
+}
+
+impl<'a, C: Ring> Mul<&'a Polynomial<C>> for &'a Polynomial<C> {
+    type Output = Polynomial<C>;
+
+    fn mul(self, other: &Polynomial<C>) -> Polynomial<C> {
+        self.checked_mul(other).unwrap_or_else(|| overflow())
+    }
+}
+
+impl<C: Ring> Neg for &Polynomial<C> {
+    type Output = Polynomial<C>;
+
+    fn neg(self) -> Polynomial<C> {
+        self.checked_neg().unwrap_or_else(|| overflow())
+    }
+}
+
+impl<C: Ring> Neg for Polynomial<C> {
+    type Output = Polynomial<C>;
+
+    fn neg(self) -> Polynomial<C> {
+        -&self
+    }
+}
+
+/// Implement the operators for owned polynomials in terms of the ones on
+/// references.
+macro_rules! forward_ops {
+    ($($op:ident, $method:ident);*) => {
+        $(
+            impl<C: Ring> $op<Polynomial<C>> for Polynomial<C> {
+                type Output = Polynomial<C>;
+
+                fn $method(self, other: Polynomial<C>) -> Polynomial<C> {
+                    (&self).$method(&other)
+                }
+            }
+
+            impl<C: Ring> $op<&Polynomial<C>> for Polynomial<C> {
+                type Output = Polynomial<C>;
+
+                fn $method(self, other: &Polynomial<C>) -> Polynomial<C> {
+                    (&self).$method(other)
+                }
+            }
+
+            impl<C: Ring> $op<Polynomial<C>> for &Polynomial<C> {
+                type Output = Polynomial<C>;
+
+                fn $method(self, other: Polynomial<C>) -> Polynomial<C> {
+                    self.$method(&other)
+                }
+            }
+        )*
+    };
+}
+
+forward_ops!(Add, add; Sub, sub; Mul, mul);
+
+impl<C: Ring> fmt::Display for Polynomial<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by_cached_key(|(m, _)| m.display_order());
+        for (i, (m, c)) in terms.into_iter().enumerate() {
+            let abs = if c.is_negative() {
+                C::zero().checked_sub(c).unwrap_or_else(|| c.clone())
+            } else {
+                c.clone()
+            };
+            match (i, c.is_negative()) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            if abs != C::one() || m.degree() == 0 {
+                write!(f, "{}", abs)?;
+            }
+            write!(f, "{}", m)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type P = Polynomial<i64>;
+
+    fn x() -> P {
+        P::var("x")
+    }
+
+    fn y() -> P {
+        P::var("y")
+    }
+
+    #[test]
+    fn test_superscript() {
+        assert_eq!(superscript_number(0), "⁰");
+        assert_eq!(superscript_number(123), "¹²³");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let one = P::constant(1);
+        assert_eq!(&x() - &x(), P::zero());
+        assert_eq!(-(&x() - &one), one - x());
+        assert_eq!(
+            (x() + y()).pow(2),
+            x().pow(2) + x() * y() * P::constant(2) + y().pow(2)
+        );
+        assert_eq!(x().pow(0), P::constant(1));
+        assert_eq!(x().scale(&-3), P::term(-3, &[("x", 1)]));
+        assert_eq!(
+            P::term(2, &[("x", 1), ("x", 2), ("y", 0)]),
+            P::term(2, &[("x", 3)])
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let p = x() * y() + y().pow(3) + P::constant(4);
+        assert_eq!(p.degree(), 3);
+        assert_eq!(p.degree_in("x"), 1);
+        assert_eq!(p.degree_in("y"), 3);
+        assert_eq!(p.degree_in("z"), 0);
+        assert!(p.is_linear_in("x"));
+        assert!(!p.is_linear_in("y"));
+        assert!(!p.is_linear());
+        assert_eq!(p.variables().into_iter().collect::<Vec<_>>(), ["x", "y"]);
+        assert_eq!(p.coeff(&Monomial::new(&[("y", 3)])), 1);
+        assert_eq!(p.coeff(&Monomial::new(&[("x", 3)])), 0);
+        assert_eq!(p.get_constant(), None);
+        assert_eq!(P::zero().get_constant(), Some(0));
+    }
+
+    #[test]
+    fn test_substitute_and_evaluate() {
+        let p = x().pow(2) + x() * y();
+        // x = y + 1 gives y² + 2y + 1 + y² + y.
+        let q = p.substitute("x", &(y() + P::constant(1)));
+        assert_eq!(q, y().pow(2).scale(&2) + y().scale(&3) + P::constant(1));
+
+        assert_eq!(
+            p.evaluate_partial(&[("x", 2)]).unwrap(),
+            y().scale(&2) + P::constant(4)
+        );
+        assert_eq!(p.evaluate(&[("x", 2), ("y", -5)]).unwrap(), -6);
+        assert!(p.evaluate(&[("x", 2)]).is_err());
+        assert!(x().pow(2).evaluate(&[("x", i64::MAX)]).is_err());
+
+        let big = Polynomial::<i128>::var("x").pow(2);
+        assert_eq!(
+            big.evaluate(&[("x", i64::MAX as i128)]).unwrap(),
+            (i64::MAX as i128).pow(2)
+        );
+    }
+
+    #[test]
+    fn test_checked() {
+        let big = P::constant(i64::MAX);
+        assert_eq!(x().checked_add(&y()), Some(x() + y()));
+        assert_eq!(big.checked_add(&P::constant(1)), None);
+        assert_eq!(big.checked_sub(&P::constant(-1)), None);
+        assert_eq!(big.checked_mul(&x().scale(&2)), None);
+        assert_eq!(P::constant(i64::MIN).checked_neg(), None);
+        assert_eq!(big.checked_neg(), Some(P::constant(-i64::MAX)));
+        assert_eq!(x().scale(&(1 << 32)).checked_pow(2), None);
+        assert_eq!(x().checked_substitute("x", &big).unwrap(), big);
+        assert_eq!(x().pow(2).checked_substitute("x", &big), None);
+        assert_eq!(
+            P::checked_from_terms([(Monomial::default(), i64::MAX), (Monomial::default(), 1)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_modular() {
+        type M = Modular<5>;
+        let x = Polynomial::<M>::var("x");
+        let p = (&x + &Polynomial::constant(M::new(1))).pow(5);
+        // The Frobenius endomorphism: (x + 1)⁵ = x⁵ + 1 mod 5.
+        assert_eq!(p, x.pow(5) + Polynomial::constant(M::new(1)));
+        assert_eq!(p.evaluate(&[("x", M::new(3))]).unwrap(), M::new(4));
+    }
+
+    #[test]
+    fn test_fmt() {
+        assert_eq!(P::zero().to_string(), "0");
+        assert_eq!((x() - P::constant(1)).to_string(), "x - 1");
+        assert_eq!(
+            (P::constant(-2) + x().pow(2) + y() * x()).to_string(),
+            "x² + xy - 2"
+        );
+        assert_eq!((-x() * y().pow(2) + x().scale(&3)).to_string(), "-xy² + 3x");
+        assert_eq!((P::var("noun") * P::var("verb")).to_string(), "noun·verb");
+    }
+}
@@ -0,0 +1,412 @@
+//! Timing and memory measurements for the runner's report mode.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::solution::{Answers, Entry};
+
+/// Global allocator that keeps track of the currently allocated and the
+/// peak number of bytes, forwarding the actual work to [System].
+pub struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        CountingAllocator {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of bytes that are currently allocated.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Highest number of allocated bytes since the last [Self::reset_peak].
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Start a new measurement by setting the peak to the current usage.
+    /// Returns the current usage.
+    pub fn reset_peak(&self) -> usize {
+        let current = self.current();
+        self.peak.store(current, Ordering::Relaxed);
+        current
+    }
+
+    fn add(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn sub(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        CountingAllocator::new()
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if new_ptr == ptr {
+            // Resized in place, the old and new blocks never coexisted.
+            self.sub(layout.size());
+            self.add(new_size);
+        } else if !new_ptr.is_null() {
+            // Both blocks were allocated while the data was copied.
+            self.add(new_size);
+            self.sub(layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// Runtime and memory usage of a single day on a single input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub year: u32,
+    pub day: u32,
+    pub suffix: String,
+    /// The stages are `None` for unsplit days, which are only timed as a
+    /// whole.
+    pub parse: Option<Duration>,
+    pub part1: Option<Duration>,
+    pub part2: Option<Duration>,
+    pub total: Duration,
+    /// Peak number of bytes allocated on top of what was allocated before
+    /// the day was started.
+    pub peak_bytes: usize,
+    pub answers: Answers,
+}
+
+/// Run a day on the given input, timing each stage and recording the peak
+/// memory usage with the global [CountingAllocator]. Unsplit days are timed
+/// with a single [Entry::solve].
+pub fn measure(
+    entry: &Entry,
+    suffix: &str,
+    input: &str,
+    allocator: &CountingAllocator,
+) -> Result<Measurement> {
+    let baseline = allocator.reset_peak();
+
+    let mut measurement = Measurement {
+        year: entry.year,
+        day: entry.day,
+        suffix: suffix.to_owned(),
+        parse: None,
+        part1: None,
+        part2: None,
+        total: Duration::ZERO,
+        peak_bytes: 0,
+        answers: Answers {
+            part1: None,
+            part2: None,
+        },
+    };
+
+    if entry.split {
+        let start = Instant::now();
+        let parsed = entry.parse(input)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        measurement.answers.part1 = entry.part1(&parsed)?;
+        let part1 = start.elapsed();
+
+        let start = Instant::now();
+        measurement.answers.part2 = entry.part2(&parsed)?;
+        let part2 = start.elapsed();
+
+        drop(parsed);
+
+        measurement.parse = Some(parse);
+        measurement.part1 = Some(part1);
+        measurement.part2 = Some(part2);
+        measurement.total = parse + part1 + part2;
+    } else {
+        let start = Instant::now();
+        measurement.answers = entry.solve(input)?;
+        measurement.total = start.elapsed();
+    }
+
+    measurement.peak_bytes = allocator.peak().saturating_sub(baseline);
+    Ok(measurement)
+}
+
+fn format_duration(d: Duration) -> String {
+    let micros = d.as_secs_f64() * 1e6;
+    if micros < 1e3 {
+        format!("{:.1}µs", micros)
+    } else if micros < 1e6 {
+        format!("{:.2}ms", micros / 1e3)
+    } else {
+        format!("{:.2}s", micros / 1e6)
+    }
+}
+
+fn format_stage(d: Option<Duration>) -> String {
+    d.map_or_else(|| "-".to_owned(), format_duration)
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+/// Sort the measurements so that the slowest days come first.
+pub fn sort_slowest_first(measurements: &mut [Measurement]) {
+    measurements.sort_by_key(|m| Reverse(m.total));
+}
+
+/// Human readable table of the measurements, in the given order.
+pub fn table(measurements: &[Measurement]) -> String {
+    let header = [
+        "day", "input", "parse", "part 1", "part 2", "total", "peak mem",
+    ];
+    let rows: Vec<[String; 7]> = measurements
+        .iter()
+        .map(|m| {
+            [
+                format!("{} d{:02}", m.year, m.day),
+                m.suffix.clone(),
+                format_stage(m.parse),
+                format_stage(m.part1),
+                format_stage(m.part2),
+                format_duration(m.total),
+                format_bytes(m.peak_bytes),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let mut write_row = |cells: &[&str]| {
+        for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            // Left-align the text columns, right-align the numbers.
+            let pad = " ".repeat(width - cell.chars().count());
+            let sep = if i == 0 { "" } else { "  " };
+            if i < 2 {
+                write!(out, "{}{}{}", sep, cell, pad).unwrap();
+            } else {
+                write!(out, "{}{}{}", sep, pad, cell).unwrap();
+            }
+        }
+        out.push('\n');
+    };
+
+    write_row(&header);
+    for row in &rows {
+        write_row(&row.each_ref().map(String::as_str));
+    }
+    out
+}
+
+fn nanos(d: Option<Duration>) -> Option<u128> {
+    d.map(|d| d.as_nanos())
+}
+
+/// CSV dump of the measurements, with durations in nanoseconds. The stages
+/// of unsplit days are left empty.
+pub fn csv(measurements: &[Measurement]) -> String {
+    let mut out = "year,day,input,parse_ns,part1_ns,part2_ns,total_ns,peak_bytes\n".to_owned();
+    for m in measurements {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            m.year,
+            m.day,
+            m.suffix,
+            nanos(m.parse).map_or(String::new(), |n| n.to_string()),
+            nanos(m.part1).map_or(String::new(), |n| n.to_string()),
+            nanos(m.part2).map_or(String::new(), |n| n.to_string()),
+            m.total.as_nanos(),
+            m.peak_bytes
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// `s` as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// JSON dump of the measurements, with durations in nanoseconds. The stages
+/// of unsplit days are `null`.
+pub fn json(measurements: &[Measurement]) -> String {
+    let entries: Vec<String> = measurements
+        .iter()
+        .map(|m| {
+            format!(
+                "  {{\"year\": {}, \"day\": {}, \"input\": {}, \"parse_ns\": {}, \"part1_ns\": {}, \"part2_ns\": {}, \"total_ns\": {}, \"peak_bytes\": {}}}",
+                m.year,
+                m.day,
+                json_string(&m.suffix),
+                nanos(m.parse).map_or("null".to_owned(), |n| n.to_string()),
+                nanos(m.part1).map_or("null".to_owned(), |n| n.to_string()),
+                nanos(m.part2).map_or("null".to_owned(), |n| n.to_string()),
+                m.total.as_nanos(),
+                m.peak_bytes
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurements() -> Vec<Measurement> {
+        vec![
+            Measurement {
+                year: 2021,
+                day: 5,
+                suffix: "full".to_owned(),
+                parse: Some(Duration::from_micros(10)),
+                part1: Some(Duration::from_millis(9)),
+                part2: Some(Duration::ZERO),
+                total: Duration::from_micros(9010),
+                peak_bytes: 1 << 20,
+                answers: (5, 12).into(),
+            },
+            Measurement {
+                year: 2021,
+                day: 12,
+                suffix: "example_1".to_owned(),
+                parse: None,
+                part1: None,
+                part2: None,
+                total: Duration::from_nanos(13000500),
+                peak_bytes: 100,
+                answers: (10, 36).into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_sort_and_table() {
+        let mut m = measurements();
+        sort_slowest_first(&mut m);
+        assert_eq!(m[0].day, 12);
+
+        let table = table(&m);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("day       input"));
+        assert!(lines[1].starts_with("2021 d12  example_1"));
+        assert!(lines[1].contains("  -  "));
+        assert!(lines[2].ends_with("1.0MiB"));
+        assert!(lines[2].contains("9.01ms"));
+    }
+
+    #[test]
+    fn test_dumps() {
+        let m = measurements();
+        assert_eq!(
+            csv(&m).lines().nth(1),
+            Some("2021,5,full,10000,9000000,0,9010000,1048576")
+        );
+        assert_eq!(
+            csv(&m).lines().nth(2),
+            Some("2021,12,example_1,,,,13000500,100")
+        );
+        assert!(json(&m).contains(
+            "{\"year\": 2021, \"day\": 12, \"input\": \"example_1\", \"parse_ns\": null, \"part1_ns\": null, \"part2_ns\": null, \"total_ns\": 13000500, \"peak_bytes\": 100}"
+        ));
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("example_1"), "\"example_1\"");
+        assert_eq!(
+            json_string("a\"b\\c\nd\u{1b}e\u{e9}"),
+            "\"a\\\"b\\\\c\\nd\\u001be\u{e9}\""
+        );
+    }
+
+    #[test]
+    fn test_realloc_peak() {
+        let allocator = CountingAllocator::new();
+        let layout = Layout::from_size_align(1000, 8).unwrap();
+        unsafe {
+            let ptr = allocator.alloc(layout);
+            assert_eq!(allocator.reset_peak(), 1000);
+
+            let new_ptr = allocator.realloc(ptr, layout, 10);
+            assert_eq!(allocator.current(), 10);
+            let expected = if new_ptr == ptr { 1000 } else { 1010 };
+            assert_eq!(allocator.peak(), expected);
+
+            allocator.dealloc(new_ptr, Layout::from_size_align(10, 8).unwrap());
+        }
+        assert_eq!(allocator.current(), 0);
+    }
+}
@@ -0,0 +1,299 @@
+//! A common interface for all the days, plus a registry that runners,
+//! benchmarks and tests can use to enumerate the solutions.
+
+use std::any::Any;
+use std::cell::OnceCell;
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+
+/// Values that can be returned as the answer to a subtask.
+pub trait Answer {
+    /// The answer as it would be entered on the website, or `None` if
+    /// the subtask is not solved in this repo.
+    fn render(&self) -> Option<String>;
+}
+
+macro_rules! impl_answer {
+    ($($t:ty),*) => {
+        $(
+            impl Answer for $t {
+                fn render(&self) -> Option<String> {
+                    Some(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_answer!(i32, i64, u32, u64, usize, String);
+
+impl Answer for Option<String> {
+    fn render(&self) -> Option<String> {
+        self.clone()
+    }
+}
+
+/// Placeholder answer for days where a subtask is not solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsolved;
+
+impl Answer for Unsolved {
+    fn render(&self) -> Option<String> {
+        None
+    }
+}
+
+/// The common interface for a single day: parse the input once, then
+/// solve both subtasks on the parsed representation.
+pub trait Solution {
+    type Input: 'static;
+    type Part1: Answer;
+    type Part2: Answer;
+
+    /// Whether parsing and the two subtasks are done separately, so that
+    /// they can be timed on their own. Only [Joint] days are unsplit.
+    const SPLIT: bool = true;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Self::Part1>;
+    fn part2(input: &Self::Input) -> Result<Self::Part2>;
+}
+
+/// Days with a single `solve` function that computes both subtasks at once.
+/// These are the days that find both answers in the same pass, so separate
+/// parts would have to repeat most of the work.
+///
+/// Use [Joint] to turn these into a [Solution]. Such a solution is unsplit:
+/// parsing only copies the input and all the work is done when the first
+/// part is requested, so its timings only make sense as a whole.
+pub trait SolveBoth {
+    fn solve(input: &str) -> Result<Answers>;
+}
+
+/// Adapter implementing [Solution] for a [SolveBoth] day.
+pub struct Joint<S>(PhantomData<S>);
+
+pub struct JointInput {
+    input: String,
+    answers: OnceCell<Result<Answers, String>>,
+}
+
+impl JointInput {
+    fn answers(&self, solve: impl FnOnce(&str) -> Result<Answers>) -> Result<&Answers> {
+        self.answers
+            .get_or_init(|| solve(&self.input).map_err(|e| format!("{:#}", e)))
+            .as_ref()
+            .map_err(|e| anyhow!("{}", e))
+    }
+}
+
+impl<S: SolveBoth> Solution for Joint<S> {
+    type Input = JointInput;
+    type Part1 = Option<String>;
+    type Part2 = Option<String>;
+
+    const SPLIT: bool = false;
+
+    /// Does no parsing, see [SolveBoth].
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(JointInput {
+            input: input.to_owned(),
+            answers: OnceCell::new(),
+        })
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1> {
+        Ok(input.answers(S::solve)?.part1.clone())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2> {
+        Ok(input.answers(S::solve)?.part2.clone())
+    }
+}
+
+/// The parsed input of a day, with its concrete type erased.
+pub struct Parsed(Box<dyn Any>);
+
+/// The rendered answers of both subtasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl<A: Answer, B: Answer> From<(A, B)> for Answers {
+    fn from((a, b): (A, B)) -> Self {
+        Answers {
+            part1: a.render(),
+            part2: b.render(),
+        }
+    }
+}
+
+/// A registered day. This wraps the [Solution] of the day with its types
+/// erased, so that all the days can be stored in a single list.
+pub struct Entry {
+    pub year: u32,
+    pub day: u32,
+    /// See [Solution::SPLIT]. Unsplit days should be timed with [Entry::solve].
+    pub split: bool,
+    parse: fn(&str) -> Result<Parsed>,
+    part1: fn(&Parsed) -> Result<Option<String>>,
+    part2: fn(&Parsed) -> Result<Option<String>>,
+}
+
+fn erased_parse<S: Solution>(input: &str) -> Result<Parsed> {
+    Ok(Parsed(Box::new(S::parse(input)?)))
+}
+
+fn downcast<S: Solution>(parsed: &Parsed) -> Result<&S::Input> {
+    parsed
+        .0
+        .downcast_ref()
+        .ok_or_else(|| anyhow!("Parsed input belongs to a different day"))
+}
+
+fn erased_part1<S: Solution>(parsed: &Parsed) -> Result<Option<String>> {
+    Ok(S::part1(downcast::<S>(parsed)?)?.render())
+}
+
+fn erased_part2<S: Solution>(parsed: &Parsed) -> Result<Option<String>> {
+    Ok(S::part2(downcast::<S>(parsed)?)?.render())
+}
+
+impl Entry {
+    pub const fn new<S: Solution>(year: u32, day: u32) -> Entry {
+        Entry {
+            year,
+            day,
+            split: S::SPLIT,
+            parse: erased_parse::<S>,
+            part1: erased_part1::<S>,
+            part2: erased_part2::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Parsed> {
+        (self.parse)(input)
+    }
+
+    pub fn part1(&self, parsed: &Parsed) -> Result<Option<String>> {
+        (self.part1)(parsed)
+    }
+
+    pub fn part2(&self, parsed: &Parsed) -> Result<Option<String>> {
+        (self.part2)(parsed)
+    }
+
+    /// Parse the input and solve both subtasks.
+    pub fn solve(&self, input: &str) -> Result<Answers> {
+        let parsed = self.parse(input)?;
+        Ok(Answers {
+            part1: self.part1(&parsed)?,
+            part2: self.part2(&parsed)?,
+        })
+    }
+}
+
+/// All registered days of all years, ordered by year and day.
+pub fn all() -> impl Iterator<Item = &'static Entry> {
+    [
+        crate::y2019::SOLUTIONS,
+        crate::y2020::SOLUTIONS,
+        crate::y2021::SOLUTIONS,
+        crate::y2022::SOLUTIONS,
+        crate::y2023::SOLUTIONS,
+        crate::y2025::SOLUTIONS,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Look up the solution for a given year and day.
+pub fn find(year: u32, day: u32) -> Option<&'static Entry> {
+    all().find(|entry| (entry.year, entry.day) == (year, day))
+}
+
+/// Register a day in the `SOLUTIONS` list of its year.
+///
+/// * `solution!(2025, 5, d05)` for days implementing [Solution] as `Day`.
+/// * `solution!(2025, 1, d01, str)` for unsplit days with `solve(&str) -> Result<(A, B)>`.
+/// * `solution!(2021, 6, d06, buf)` for days with `solve(impl BufRead) -> Result<(A, B)>`.
+#[macro_export]
+macro_rules! solution {
+    ($year:literal, $day:literal, $module:ident) => {
+        $crate::solution::Entry::new::<$module::Day>($year, $day)
+    };
+    ($year:literal, $day:literal, $module:ident, $style:ident) => {{
+        struct Wrapper;
+        impl $crate::solution::SolveBoth for Wrapper {
+            fn solve(input: &str) -> anyhow::Result<$crate::solution::Answers> {
+                Ok($crate::solution!(@call $module, $style, input)?.into())
+            }
+        }
+        $crate::solution::Entry::new::<$crate::solution::Joint<Wrapper>>($year, $day)
+    }};
+    (@call $module:ident, str, $input:expr) => {
+        $module::solve($input)
+    };
+    (@call $module:ident, buf, $input:expr) => {
+        $module::solve($input.as_bytes())
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_registry_is_sorted() {
+        let keys: Vec<_> = all().map(|entry| (entry.year, entry.day)).collect();
+        assert!(keys.iter().tuple_windows().all(|(a, b)| a < b));
+        assert!(find(2025, 9).is_some());
+        assert!(find(2021, 19).is_none());
+    }
+
+    #[test]
+    fn test_parsed_input_is_checked() -> Result<()> {
+        let a = find(2019, 1).unwrap();
+        let b = find(2021, 1).unwrap();
+        let parsed = a.parse("12\n14\n")?;
+        assert_eq!(a.part1(&parsed)?, Some("4".to_owned()));
+        assert!(b.part1(&parsed).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_answer() -> Result<()> {
+        let entry = find(2020, 25).unwrap();
+        let answers = entry.solve("5764801\n17807724\n")?;
+        assert_eq!(answers.part1, Some("14897079".to_owned()));
+        assert_eq!(answers.part2, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() -> Result<()> {
+        for (day, split) in [
+            (1, false),
+            (5, true),
+            (12, true),
+            (15, true),
+            (16, true),
+            (17, false),
+            (22, true),
+            (23, true),
+            (24, false),
+        ] {
+            assert_eq!(find(2021, day).unwrap().split, split);
+        }
+
+        let entry = find(2021, 5).unwrap();
+        let parsed = entry.parse("0,9 -> 5,9\n8,0 -> 0,8\n0,9 -> 2,9\n0,0 -> 8,8\n")?;
+        assert_eq!(entry.part1(&parsed)?, Some("3".to_owned()));
+        assert_eq!(entry.part2(&parsed)?, Some("4".to_owned()));
+        Ok(())
+    }
+}
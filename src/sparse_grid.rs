@@ -0,0 +1,187 @@
+//! Unbounded 2D grids where all but finitely many cells share a background
+//! value, e.g. for cellular automata that grow in every direction.
+
+use std::collections::HashMap;
+
+use crate::grid::Grid;
+
+/// A position in a sparse grid, `(x, y)` with `y` pointing downwards.
+pub type Pos = (i64, i64);
+
+/// An inclusive rectangle of positions.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Bounds {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Bounds {
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    /// The smallest bounds containing both `self` and `pos`.
+    pub fn including(self, (x, y): Pos) -> Bounds {
+        Bounds {
+            min: (self.min.0.min(x), self.min.1.min(y)),
+            max: (self.max.0.max(x), self.max.1.max(y)),
+        }
+    }
+
+    /// The bounds grown by `margin` cells on every side.
+    pub fn expand(self, margin: i64) -> Bounds {
+        Bounds {
+            min: (self.min.0 - margin, self.min.1 - margin),
+            max: (self.max.0 + margin, self.max.1 + margin),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1) as usize
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(self) -> impl Iterator<Item = Pos> {
+        (self.min.1..=self.max.1).flat_map(move |y| (self.min.0..=self.max.0).map(move |x| (x, y)))
+    }
+}
+
+/// An infinite grid that only stores the cells which differ from the
+/// background value.
+///
+/// The background can be changed at any time, which changes the value of
+/// every cell that wasn't explicitly set to something else. This is what
+/// e.g. 2021 day 20 needs when the empty space toggles on and off.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    background: T,
+    cells: HashMap<Pos, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    pub fn new(background: T) -> SparseGrid<T> {
+        SparseGrid {
+            background,
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    /// Places a dense grid with its top left corner at the origin.
+    pub fn from_grid(grid: &Grid<T>, background: T) -> SparseGrid<T> {
+        let mut sparse = SparseGrid::new(background);
+        for ((x, y), value) in grid.iter() {
+            sparse.set((x as i64, y as i64), value.clone());
+        }
+        sparse
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    /// Change the value of every cell that is not explicitly set.
+    pub fn set_background(&mut self, background: T) {
+        self.cells.retain(|_, value| *value != background);
+        self.background = background;
+    }
+
+    pub fn get(&self, pos: Pos) -> &T {
+        self.cells.get(&pos).unwrap_or(&self.background)
+    }
+
+    pub fn set(&mut self, pos: Pos, value: T) {
+        if value == self.background {
+            self.cells.remove(&pos);
+        } else {
+            self.cells.insert(pos, value);
+            self.bounds = Some(match self.bounds {
+                Some(bounds) => bounds.including(pos),
+                None => Bounds { min: pos, max: pos },
+            });
+        }
+    }
+
+    /// A rectangle outside of which every cell has the background value, or
+    /// `None` if no cell was ever set. The bounds only ever grow, so they
+    /// might not be tight after cells were reset to the background.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Number of cells that differ from the background.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The cells that differ from the background, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    /// Number of non-background cells matching the predicate. Note that if
+    /// the background matches, there are infinitely many matching cells.
+    pub fn count(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.cells.values().filter(|v| pred(v)).count()
+    }
+
+    /// Copy the cells within `bounds` into a dense grid.
+    pub fn to_grid(&self, bounds: Bounds) -> Grid<T> {
+        Grid::from_fn(bounds.width(), bounds.height(), |(x, y)| {
+            self.get((bounds.min.0 + x as i64, bounds.min.1 + y as i64))
+                .clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background() {
+        let mut grid = SparseGrid::new(false);
+        assert_eq!(grid.bounds(), None);
+        grid.set((-3, 2), true);
+        grid.set((4, -1), true);
+        grid.set((0, 0), false);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds {
+                min: (-3, -1),
+                max: (4, 2)
+            })
+        );
+        assert!(*grid.get((-3, 2)));
+        assert!(!*grid.get((1000, 1000)));
+
+        grid.set_background(true);
+        assert!(grid.is_empty());
+        assert!(*grid.get((1000, 1000)));
+
+        grid.set((1, 1), false);
+        assert_eq!(grid.count(|v| !v), 1);
+    }
+
+    #[test]
+    fn test_to_grid() {
+        let dense = Grid::from_rows(vec![vec![1, 0], vec![0, 2]]).unwrap();
+        let sparse = SparseGrid::from_grid(&dense, 0);
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.to_grid(sparse.bounds().unwrap()), dense);
+
+        let bounds = sparse.bounds().unwrap().expand(1);
+        assert_eq!(bounds.positions().count(), 16);
+        assert_eq!(sparse.to_grid(bounds).count(|&v| v == 0), 14);
+    }
+}
@@ -0,0 +1,12 @@
+//! Helpers shared by the tests.
+
+/// A small deterministic random generator: a linear congruential generator
+/// returning values in `0..m`.
+pub fn lcg(mut seed: u64) -> impl FnMut(u64) -> u64 {
+    move |m: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) % m
+    }
+}
@@ -1,75 +1,104 @@
 /// [Union-Find](https://en.wikipedia.org/wiki/Disjoint-set_data_structure) data
 /// structure where every set has some associated data.
+///
+/// Sets are merged by size and lookups compress paths. Besides the data, the
+/// structure keeps track of the number of sets, their sizes and their
+/// members, the latter as a circular linked list so that merging stays O(1).
 #[derive(Debug, Clone)]
 pub struct UnionFind<T> {
-    // Misusing [std::result::Result] as a general purpose Either type.
-    data: Vec<Result<T, SetIndex>>,
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    /// The next member of the same set, the members form a cycle.
+    next: Vec<usize>,
+    /// Only the roots have data.
+    data: Vec<Option<T>>,
+    nr_sets: usize,
 }
 
 impl<T> UnionFind<T> {
     /// Constructs a new, empty [UnionFind<T>].
     pub fn new() -> UnionFind<T> {
-        UnionFind { data: Vec::new() }
+        UnionFind::with_capacity(0)
     }
 
     /// Constructs a new, empty [UnionFind<T>] with the specified capacity.
     pub fn with_capacity(size: usize) -> UnionFind<T> {
         UnionFind {
+            parents: Vec::with_capacity(size),
+            sizes: Vec::with_capacity(size),
+            next: Vec::with_capacity(size),
             data: Vec::with_capacity(size),
+            nr_sets: 0,
         }
     }
 
+    /// Constructs `n` singleton sets, where the set with index `i` has the
+    /// data `f(i)`.
+    pub fn from_fn(n: usize, f: impl FnMut(usize) -> T) -> UnionFind<T> {
+        let mut uf = UnionFind::with_capacity(n);
+        for data in (0..n).map(f) {
+            uf.new_set(data);
+        }
+        uf
+    }
+
     /// Add a new set to the structure.
     pub fn new_set(&mut self, data: T) -> SetIndex {
-        let set = SetIndex(self.data.len());
-        self.data.push(Ok(data));
-        set
+        let set = self.parents.len();
+        self.parents.push(set);
+        self.sizes.push(1);
+        self.next.push(set);
+        self.data.push(Some(data));
+        self.nr_sets += 1;
+        SetIndex(set)
     }
 
     /// Merge two sets, using the provided function to combine the
-    /// data for the two sets.
-    pub fn merge_with(&mut self, a: SetIndex, b: SetIndex, merger: impl FnOnce(T, T) -> T) {
-        let root_a = self.lookup_root_optimize(a);
-        let root_b = self.lookup_root_optimize(b);
+    /// data for the two sets. Returns false if they already were the same
+    /// set.
+    pub fn merge_with(&mut self, a: SetIndex, b: SetIndex, merger: impl FnOnce(T, T) -> T) -> bool {
+        let root_a = self.lookup_root_optimize(a).0;
+        let root_b = self.lookup_root_optimize(b).0;
 
         if root_a == root_b {
-            return;
+            return false;
         }
 
-        // We use temporary values so that we can swap the associated data out
-        // of the data vec without copying. Both of these entires are overwritten
-        // later, therefore the temporary values will never show up for the user.
-        let mut data_a = Err(SetIndex(usize::MAX));
-        let mut data_b = Err(SetIndex(usize::MAX));
-        std::mem::swap(&mut self.data[root_a.0], &mut data_a);
-        std::mem::swap(&mut self.data[root_b.0], &mut data_b);
-        let data = merger(data_a.unwrap(), data_b.unwrap());
+        let data_a = self.data[root_a].take().unwrap();
+        let data_b = self.data[root_b].take().unwrap();
+        let data = merger(data_a, data_b);
 
-        self.data[root_a.0] = Err(root_b);
-        self.data[root_b.0] = Ok(data);
+        // Attach the smaller tree below the larger one.
+        let (root, child) = if self.sizes[root_a] < self.sizes[root_b] {
+            (root_b, root_a)
+        } else {
+            (root_a, root_b)
+        };
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        self.data[root] = Some(data);
+        // Splice the two member cycles into one.
+        self.next.swap(root_a, root_b);
+        self.nr_sets -= 1;
+        true
     }
 
     fn lookup_root(&self, mut set: SetIndex) -> SetIndex {
-        while let Err(parent) = self.data[set.0] {
-            set = parent;
+        while self.parents[set.0] != set.0 {
+            set = SetIndex(self.parents[set.0]);
         }
         set
     }
 
-    fn lookup_root_optimize(&mut self, mut set: SetIndex) -> SetIndex {
-        let mut stack = Vec::new();
-
-        while let Err(parent) = self.data[set.0] {
-            stack.push(set);
-            set = parent;
-        }
+    fn lookup_root_optimize(&mut self, set: SetIndex) -> SetIndex {
+        let root = self.lookup_root(set);
 
-        // the last entry on [stack] already points to the correct parent.
-        for s in &stack[..stack.len().saturating_sub(1)] {
-            self.data[s.0] = Err(set);
+        let mut current = set.0;
+        while current != root.0 {
+            current = std::mem::replace(&mut self.parents[current], root.0);
         }
 
-        set
+        root
     }
 
     /// Compact all parent pointer datastructure in order to
@@ -77,11 +106,21 @@ impl<T> UnionFind<T> {
     /// pointer directly points to the root of the set: either
     /// to itself or to the root index.
     pub fn optimize(&mut self) {
-        for i in 0..self.data.len() {
+        for i in 0..self.parents.len() {
             self.lookup_root_optimize(SetIndex(i));
         }
     }
 
+    /// The representative of the set containing `set`, all members of a set
+    /// have the same one.
+    pub fn find(&mut self, set: SetIndex) -> SetIndex {
+        self.lookup_root_optimize(set)
+    }
+
+    pub fn same_set(&self, a: SetIndex, b: SetIndex) -> bool {
+        self.lookup_root(a) == self.lookup_root(b)
+    }
+
     /// Get an immutable reference to the data associated with a set.
     pub fn get(&self, set: SetIndex) -> &T {
         self.data[self.lookup_root(set).0].as_ref().unwrap()
@@ -92,6 +131,54 @@ impl<T> UnionFind<T> {
         let idx = self.lookup_root_optimize(set).0;
         self.data[idx].as_mut().unwrap()
     }
+
+    /// Number of elements, i.e. of calls to [Self::new_set].
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn nr_sets(&self) -> usize {
+        self.nr_sets
+    }
+
+    /// Number of elements in the set containing `set`.
+    pub fn size(&self, set: SetIndex) -> usize {
+        self.sizes[self.lookup_root(set).0]
+    }
+
+    /// The sizes of all sets, in the same order as the iterator.
+    pub fn sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.into_iter().map(|(set, _)| self.sizes[set.0])
+    }
+
+    /// All elements in the same set as `set`, starting with `set` itself.
+    pub fn members(&self, set: SetIndex) -> impl Iterator<Item = SetIndex> + '_ {
+        let mut current = Some(set.0);
+        std::iter::from_fn(move || {
+            let out = current?;
+            let next = self.next[out];
+            current = (next != set.0).then_some(next);
+            Some(SetIndex(out))
+        })
+    }
+}
+
+impl UnionFind<()> {
+    /// Constructs `n` singleton sets without data, the set for element `i`
+    /// has the index `i`, see [SetIndex::from].
+    pub fn with_sets(n: usize) -> UnionFind<()> {
+        UnionFind::from_fn(n, |_| ())
+    }
+
+    /// Merge two sets. Returns false if they already were the same set.
+    pub fn union(&mut self, a: SetIndex, b: SetIndex) -> bool {
+        self.merge_with(a, b, |_, _| ())
+    }
 }
 
 impl<T> Default for UnionFind<T> {
@@ -112,9 +199,22 @@ impl<'a, T> IntoIterator for &'a UnionFind<T> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct SetIndex(usize);
 
+impl SetIndex {
+    /// The position of the element in the order the sets were created.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for SetIndex {
+    fn from(idx: usize) -> SetIndex {
+        SetIndex(idx)
+    }
+}
+
 pub struct UnionFindIterator<'a, T> {
     uf: &'a UnionFind<T>,
     idx: Option<SetIndex>,
@@ -124,10 +224,10 @@ impl<'a, T> UnionFindIterator<'a, T> {
     pub fn advance(&mut self) {
         if let Some(SetIndex(mut idx)) = self.idx {
             idx += 1;
-            while idx < self.uf.data.len() && self.uf.data[idx].is_err() {
+            while idx < self.uf.len() && self.uf.data[idx].is_none() {
                 idx += 1;
             }
-            self.idx = if idx >= self.uf.data.len() {
+            self.idx = if idx >= self.uf.len() {
                 None
             } else {
                 Some(SetIndex(idx))
@@ -141,7 +241,9 @@ impl<'a, T> Iterator for UnionFindIterator<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(idx) = self.idx {
-            if self.uf.data[idx.0].is_err() {
+            if idx.0 >= self.uf.len() {
+                self.idx = None;
+            } else if self.uf.data[idx.0].is_none() {
                 self.advance();
             }
         }
@@ -154,6 +256,102 @@ impl<'a, T> Iterator for UnionFindIterator<'a, T> {
     }
 }
 
+/// A union-find over the elements `0..n` that can undo merges, e.g. for
+/// offline connectivity queries, see [crate::dynamic_connectivity].
+///
+/// Without path compression every merge changes a single parent pointer, so
+/// undoing it is cheap. Union by rank keeps lookups at O(log n) anyway.
+#[derive(Debug, Clone)]
+pub struct RollbackUnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    sizes: Vec<usize>,
+    nr_sets: usize,
+    /// The merges so far, as `(child, root, rank of root increased)`.
+    history: Vec<(usize, usize, bool)>,
+}
+
+/// The state of a [RollbackUnionFind] to return to with
+/// [RollbackUnionFind::rollback].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Snapshot(usize);
+
+impl RollbackUnionFind {
+    pub fn new(n: usize) -> RollbackUnionFind {
+        RollbackUnionFind {
+            parents: (0..n).collect(),
+            ranks: vec![0; n],
+            sizes: vec![1; n],
+            nr_sets: n,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn find(&self, mut a: usize) -> usize {
+        while self.parents[a] != a {
+            a = self.parents[a];
+        }
+        a
+    }
+
+    pub fn same_set(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of disjoint sets.
+    pub fn nr_sets(&self) -> usize {
+        self.nr_sets
+    }
+
+    /// Number of elements in the set containing `a`.
+    pub fn size(&self, a: usize) -> usize {
+        self.sizes[self.find(a)]
+    }
+
+    /// Merge the sets containing `a` and `b`. Returns false if they already
+    /// were the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut root, mut child) = (self.find(a), self.find(b));
+        if root == child {
+            return false;
+        }
+        if self.ranks[root] < self.ranks[child] {
+            std::mem::swap(&mut root, &mut child);
+        }
+        let rank_increased = self.ranks[root] == self.ranks[child];
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        self.ranks[root] += rank_increased as u8;
+        self.nr_sets -= 1;
+        self.history.push((child, root, rank_increased));
+        true
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.history.len())
+    }
+
+    /// Undo all merges since the snapshot was taken. Snapshots taken after
+    /// it become invalid.
+    pub fn rollback(&mut self, snapshot: Snapshot) {
+        assert!(snapshot.0 <= self.history.len(), "Invalid snapshot");
+        for (child, root, rank_increased) in self.history.drain(snapshot.0..).rev() {
+            self.parents[child] = child;
+            self.sizes[root] -= self.sizes[child];
+            self.ranks[root] -= rank_increased as u8;
+            self.nr_sets += 1;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,4 +379,63 @@ mod tests {
         assert_eq!(vec[1].1, &9);
         assert!(vec[1].0 == idx_5 || vec[1].0 == idx_9);
     }
+
+    #[test]
+    fn test_sets() {
+        let mut uf = UnionFind::with_sets(6);
+        let s = SetIndex::from;
+        assert_eq!(uf.nr_sets(), 6);
+        assert!(uf.union(s(0), s(1)));
+        assert!(uf.union(s(2), s(3)));
+        assert!(uf.union(s(1), s(3)));
+        assert!(!uf.union(s(0), s(2)));
+
+        assert_eq!(uf.len(), 6);
+        assert_eq!(uf.nr_sets(), 3);
+        assert!(uf.same_set(s(0), s(3)));
+        assert!(!uf.same_set(s(0), s(4)));
+        assert_eq!(uf.find(s(2)), uf.find(s(1)));
+        assert_eq!(uf.size(s(2)), 4);
+
+        let mut sizes: Vec<_> = uf.sizes().collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, [1, 1, 4]);
+
+        let mut members: Vec<_> = uf.members(s(3)).map(SetIndex::index).collect();
+        assert_eq!(members[0], 3);
+        members.sort_unstable();
+        assert_eq!(members, [0, 1, 2, 3]);
+        assert_eq!(uf.members(s(5)).collect::<Vec<_>>(), [s(5)]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let uf = UnionFind::<()>::new();
+        assert!(uf.is_empty());
+        assert_eq!(uf.into_iter().count(), 0);
+        assert_eq!(UnionFind::from_fn(3, |i| i * 2).get(SetIndex(2)), &4);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut uf = RollbackUnionFind::new(5);
+        uf.union(0, 1);
+        let snapshot = uf.snapshot();
+        assert!(uf.union(2, 3));
+        assert!(uf.union(1, 3));
+        assert!(!uf.union(0, 2));
+        assert_eq!(uf.nr_sets(), 2);
+        assert_eq!(uf.size(3), 4);
+
+        uf.rollback(snapshot);
+        assert_eq!(uf.nr_sets(), 4);
+        assert!(uf.same_set(0, 1));
+        assert!(!uf.same_set(1, 2));
+        assert!(!uf.same_set(2, 3));
+        assert_eq!(uf.size(0), 2);
+        assert_eq!(uf.size(3), 1);
+
+        uf.rollback(Snapshot(0));
+        assert_eq!(uf.nr_sets(), 5);
+    }
 }
@@ -0,0 +1,172 @@
+//! Optional visualisation of intermediate states.
+//!
+//! Days call [emit] with a closure producing a [Frame]. Unless the runner has
+//! installed a [Sink], e.g. with `--visualise`, the closure is never called,
+//! so emitting frames costs next to nothing in normal runs and benchmarks.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::grid::Grid;
+
+/// A single picture, rendered as text with one character per cell.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Frame {
+    /// A full grid of characters.
+    Grid(Grid<char>),
+    /// A set of points drawn with `#` on an empty background, cropped to
+    /// their bounding box.
+    Points(Vec<(i64, i64)>),
+    /// A sequence of points drawn in order, `S` marks the start and `E` the
+    /// end of the path.
+    Path(Vec<(i64, i64)>),
+}
+
+impl Frame {
+    /// A frame showing any grid, with `f` choosing the character of a cell.
+    pub fn grid<T>(grid: &Grid<T>, f: impl FnMut(&T) -> char) -> Frame {
+        Frame::Grid(grid.map(f))
+    }
+
+    pub fn points(points: impl IntoIterator<Item = (i64, i64)>) -> Frame {
+        Frame::Points(points.into_iter().collect())
+    }
+
+    /// Draw the points onto a grid covering their bounding box.
+    fn draw(points: &[(i64, i64)], mut cell: impl FnMut(usize) -> char) -> Grid<char> {
+        let Some(&first) = points.first() else {
+            return Grid::new(0, 0, ' ');
+        };
+        let (min, max) = points.iter().fold((first, first), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        });
+        let width = (max.0 - min.0 + 1) as usize;
+        let height = (max.1 - min.1 + 1) as usize;
+
+        let mut grid = Grid::new(width, height, ' ');
+        for (i, &(x, y)) in points.iter().enumerate() {
+            grid[((x - min.0) as usize, (y - min.1) as usize)] = cell(i);
+        }
+        grid
+    }
+
+    pub fn render(&self) -> String {
+        match self {
+            Frame::Grid(grid) => grid.render(|&c| c),
+            Frame::Points(points) => Frame::draw(points, |_| '#').render(|&c| c),
+            Frame::Path(path) => {
+                let last = path.len().saturating_sub(1);
+                Frame::draw(path, |i| match i {
+                    0 => 'S',
+                    i if i == last => 'E',
+                    _ => '#',
+                })
+                .render(|&c| c)
+            }
+        }
+    }
+}
+
+/// Where emitted frames end up.
+pub trait Sink: Send {
+    fn frame(&mut self, frame: &Frame) -> io::Result<()>;
+}
+
+/// Animates the frames in the terminal by redrawing the screen.
+pub struct Terminal {
+    pub delay: Duration,
+}
+
+impl Sink for Terminal {
+    fn frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        // Clear the screen and move the cursor to the top left corner.
+        write!(out, "\x1b[2J\x1b[H{}", frame.render())?;
+        out.flush()?;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+}
+
+/// Writes every frame to its own numbered text file in a directory.
+pub struct Files {
+    dir: PathBuf,
+    next: usize,
+}
+
+impl Files {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Files> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Files { dir, next: 0 })
+    }
+}
+
+impl Sink for Files {
+    fn frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let path = self.dir.join(format!("frame_{:05}.txt", self.next));
+        self.next += 1;
+        fs::write(path, frame.render())
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SINK: Mutex<Option<Box<dyn Sink>>> = Mutex::new(None);
+
+/// Install the sink that receives all frames from now on, or turn the
+/// visualisation off with `None`.
+pub fn set_sink(sink: Option<Box<dyn Sink>>) {
+    let mut current = SINK.lock().unwrap();
+    ENABLED.store(sink.is_some(), Ordering::Relaxed);
+    *current = sink;
+}
+
+/// Whether a sink is installed. Useful to skip work that is only needed to
+/// produce frames.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Send a frame to the installed sink. If the sink fails, the error is
+/// printed and the visualisation is turned off, without failing the day.
+pub fn emit(frame: impl FnOnce() -> Frame) {
+    if !enabled() {
+        return;
+    }
+    let mut sink = SINK.lock().unwrap();
+    if let Some(s) = sink.as_mut() {
+        if let Err(e) = s.frame(&frame()) {
+            eprintln!("Visualisation failed, disabling it: {}", e);
+            ENABLED.store(false, Ordering::Relaxed);
+            *sink = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let grid = Grid::from_rows(vec![vec![true, false], vec![false, true]]).unwrap();
+        assert_eq!(
+            Frame::grid(&grid, |&b| if b { '#' } else { '.' }).render(),
+            "#.\n.#\n"
+        );
+        assert_eq!(
+            Frame::points([(3, -1), (5, 0), (3, 0)]).render(),
+            "#  \n# #\n"
+        );
+        assert_eq!(
+            Frame::Path(vec![(0, 0), (1, 0), (1, 1), (2, 1)]).render(),
+            "S# \n #E\n"
+        );
+        assert_eq!(Frame::points([]).render(), "");
+    }
+}
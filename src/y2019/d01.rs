@@ -2,6 +2,7 @@ use anyhow::Result;
 use std::iter;
 
 use crate::io::parse_entries;
+use crate::solution::Solution;
 
 fn fuel(mass: &i32) -> i32 {
     mass / 3 - 2
@@ -26,3 +27,23 @@ pub fn solve(input: &str) -> Result<(i32, i32)> {
     let nums = parse_entries(input, '\n')?;
     Ok((solve_a(&nums), solve_b(&nums)))
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<i32>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_entries(input, '\n')
+    }
+
+    fn part1(nums: &Self::Input) -> Result<i32> {
+        Ok(solve_a(nums))
+    }
+
+    fn part2(nums: &Self::Input) -> Result<i32> {
+        Ok(solve_b(nums))
+    }
+}
@@ -1,117 +1,129 @@
-use crate::{bivariate_polynomial::BP, io::parse_entries};
+use crate::bivariate_polynomial::BP;
+use crate::intcode::symbolic::{Cell, Outcome, SymbolicMachine};
+use crate::intcode::{parse_program, Machine, State};
+use crate::solution::Solution;
 use anyhow::{bail, Result};
-use std::cmp::Ordering;
-
-fn access(program: &[BP], instruction_pointer: usize) -> Result<usize, String> {
-    let val = &program[instruction_pointer];
-    val.get_constant()
-        .ok_or(format!("Value not constant: {}", val))
-}
-
-fn run(program: &mut [BP]) -> Result<(), String> {
-    let mut instruction_pointer = 0;
-
-    while instruction_pointer < program.len() {
-        let instruction = access(program, instruction_pointer)?;
-
-        match instruction {
-            1 | 2 => {
-                let v1 = &program[access(program, instruction_pointer + 1).unwrap_or(0)];
-                let v2 = &program[access(program, instruction_pointer + 2).unwrap_or(0)];
-                let dest = access(program, instruction_pointer + 3)?;
-
-                program[dest] = if instruction == 1 { v1 + v2 } else { v1 * v2 };
-            }
-            99 => return Ok(()),
-            c => return Err(format!("Unknown opcode {}", c)),
-        }
-
-        instruction_pointer += 4;
+use nalgebra::Vector2;
+
+const TARGET: i64 = 19690720;
+
+/// Run the program with the given noun and verb, returning the value at
+/// position 0.
+fn run(program: &[i64], noun: i64, verb: i64) -> Result<i64> {
+    let mut machine = Machine::new(program.to_vec());
+    machine.write(1, noun)?;
+    machine.write(2, verb)?;
+    if machine.run()? != State::Halted {
+        bail!("Program tried to read an input");
     }
-
-    Err("Reached end of program".to_owned())
+    Ok(machine.read(0))
 }
 
-fn requires_brute_force(program: &[usize]) -> bool {
-    // Iterate only over the output addresses.
-    for (i, f) in program.iter().enumerate().skip(3).step_by(4) {
-        if *f > i {
-            // Program will write to an address containing code
-            // that hasn't yet been read.
-            return true;
+/// The value at position 0 as a polynomial in the noun x and the verb y, or
+/// `None` if it can't be computed symbolically.
+fn symbolic_output(program: &[i64]) -> Result<Option<BP>> {
+    let mut machine = SymbolicMachine::new(program);
+    machine.set(1, BP::x())?;
+    machine.set(2, BP::y())?;
+
+    match (machine.run()?, machine.cell(0)) {
+        (Outcome::Halted, Cell::Known(output)) => Ok(Some(output)),
+        (Outcome::Halted, Cell::Unknown { ip }) => {
+            println!("    Output depends on a symbolic read at {}", ip);
+            Ok(None)
+        }
+        (Outcome::NeedsConcrete { ip, reason }, _) => {
+            println!("    Symbolic execution stopped at {}: {}", ip, reason);
+            Ok(None)
         }
     }
-
-    // For the 2 inputs to this puzzle i've seen so far, we always have
-    // the property that the first output is written to address 3, after
-    // which we never use the output before overwriting it. This, together
-    // with the previously observed property, means that after the first
-    // instruction we never again have a noun or verb dependent address to
-    // read or write from. The end result is thus a multivariate polynomial
-    // in the noun and verb.
-    program[3] != 3 || program[5] == 3 || program[6] == 3 || program[7] != 3
-}
-
-fn load_program(input: &str, check_brute_force: bool) -> Result<Vec<BP>> {
-    let program = parse_entries(input, ',')?;
-
-    if check_brute_force && requires_brute_force(&program) {
-        bail!("This program has address-dependent reads and the task thus has to be solved by brute force.")
-    }
-
-    Ok(program.into_iter().map(BP::constant).collect())
 }
 
-pub fn solve(input: &str) -> Result<(usize, i64)> {
-    println!("Day 02");
-
-    let mut program = load_program(input, true)?;
-
-    program[1] = BP::x();
-    program[2] = BP::y();
-
-    run(&mut program).unwrap();
-
-    println!("    Value at position 0: {}", program[0]);
-
-    let sol_a = program[0].evaluate(12, 2);
-    println!("    Evaluated with x = 12, y = 2: {}\n", sol_a);
-
-    if !program[0].is_linear() {
-        panic!("Nonlinear Diophantine equation, requires brute force.");
+/// Solve the linear diophantine equation given by the output polynomial,
+/// with noun and verb being valid addresses.
+fn solve_linear(output: &BP, max: i64) -> Option<(i64, i64)> {
+    if !output.is_linear() {
+        println!("    Nonlinear Diophantine equation");
+        return None;
     }
 
     // We're left with a linear Diophantine equation in two variables, i.e.
     // ax + by = c
     // where all variables are integer.
-    let a = program[0].get_coeff(1, 0) as i64;
-    let b = program[0].get_coeff(0, 1) as i64;
-    let c = 19690720 - program[0].get_coeff(0, 0) as i64;
+    let a = output.get_coeff(1, 0);
+    let b = output.get_coeff(0, 1);
+    let c = TARGET.checked_sub(output.get_coeff(0, 0))?;
 
     println!("    Solving diophantine equation {}x + {}y = {}", a, b, c);
 
-    let dsol = crate::diophantine::linear_equation(a, b, c).unwrap();
+    let dsol = crate::diophantine::linear_equation(a, b, c)?;
 
     println!(
         "    Solution is in ({}, {}) + k({}, {}) with k in Z",
         dsol.offset.x, dsol.offset.y, dsol.step.x, dsol.step.y
     );
 
-    let k_min = -dsol.offset.x / dsol.step.x;
-    let k_max = -dsol.offset.y / dsol.step.y;
+    let k = dsol.k_range(0..=max, 0..=max)?;
+    println!("    k is in [{}, {}]", k.start(), k.end());
+
+    // There should only be one solution, otherwise take the smallest answer.
+    let xy = dsol.minimise(0..=max, 0..=max, Vector2::new(100, 1))?;
+    Some((xy.x, xy.y))
+}
+
+fn brute_force(program: &[i64], max: i64) -> Result<(i64, i64)> {
+    println!("    Falling back to brute force");
+    for noun in 0..=max {
+        for verb in 0..=max {
+            if run(program, noun, verb).ok() == Some(TARGET) {
+                return Ok((noun, verb));
+            }
+        }
+    }
+    bail!("No noun and verb produce {}", TARGET)
+}
+
+pub fn solve(input: &str) -> Result<(i64, i64)> {
+    println!("Day 02");
+
+    let program = Day::parse(input)?;
+    Ok((Day::part1(&program)?, Day::part2(&program)?))
+}
+
+pub struct Day;
 
-    println!("    k is in [{}, {}]", k_min, k_max);
+impl Solution for Day {
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
 
-    let xy = match (-dsol.step.y).cmp(&dsol.step.x) {
-        Ordering::Greater => dsol.offset + dsol.step * k_max,
-        Ordering::Equal => dsol.offset + dsol.step * (k_max + k_min) / 2,
-        Ordering::Less => dsol.offset + dsol.step * k_min,
-    };
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_program(input)
+    }
 
-    let sol_b = xy.x * 100 + xy.y;
-    println!("    x = {}, y = {}. Solution = {}", xy.x, xy.y, sol_b);
+    fn part1(program: &Self::Input) -> Result<i64> {
+        run(program, 12, 2)
+    }
 
-    Ok((sol_a, sol_b))
+    fn part2(program: &Self::Input) -> Result<i64> {
+        // Noun and verb are used as addresses, so they have to be in range.
+        let max = program.len() as i64 - 1;
+        let linear = match symbolic_output(program)? {
+            Some(output) => {
+                println!("    Value at position 0: {}", output);
+                solve_linear(&output, max).filter(|&(x, y)| run(program, x, y).ok() == Some(TARGET))
+            }
+            None => None,
+        };
+        let (x, y) = match linear {
+            Some(xy) => xy,
+            None => brute_force(program, max)?,
+        };
+
+        let sol_b = x * 100 + y;
+        println!("    x = {}, y = {}. Solution = {}", x, y, sol_b);
+        Ok(sol_b)
+    }
 }
 
 #[cfg(test)]
@@ -120,15 +132,33 @@ mod tests {
 
     #[test]
     fn test_example_endstate() -> Result<()> {
-        let mut program = load_program("1,9,10,3,2,3,11,0,99,30,40,50", false)?;
+        let program = parse_program("1,9,10,3,2,3,11,0,99,30,40,50")?;
 
-        run(&mut program).unwrap();
-        let endstate = program
+        let mut machine = SymbolicMachine::new(&program);
+        assert_eq!(machine.run()?, Outcome::Halted);
+        let endstate = machine
+            .memory()
             .iter()
-            .map(|c| c.get_constant().unwrap())
-            .collect::<Vec<usize>>();
+            .map(|c| match c {
+                Cell::Known(value) => value.get_constant().unwrap(),
+                Cell::Unknown { .. } => panic!("Unknown cell"),
+            })
+            .collect::<Vec<i64>>();
         assert_eq!(endstate, vec![3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]);
 
         Ok(())
     }
+
+    #[test]
+    fn test_brute_force_fallback() -> Result<()> {
+        // [0] = [noun] + [verb], which the symbolic run can't follow since
+        // the result depends on what's stored at the addresses.
+        let mut program = vec![1, 0, 0, 0, 99];
+        program.resize(100, 0);
+        program[50] = 19690000;
+        program[60] = 720;
+        assert_eq!(symbolic_output(&program)?, None);
+        assert_eq!(brute_force(&program, 99)?, (50, 60));
+        Ok(())
+    }
 }
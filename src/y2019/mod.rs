@@ -2,13 +2,10 @@ pub mod d01;
 pub mod d02;
 pub mod d04;
 
-#[cfg(test)]
-mod tests {
-    use crate::test_task;
+use crate::{solution, solution::Entry};
 
-    test_task! { y2019, d01, full, (3305115, 4954799) }
-
-    test_task! { y2019, d02, full, (3562624, 8298) }
-
-    test_task! { y2019, d04, full, (1873, 1264) }
-}
+pub const SOLUTIONS: &[Entry] = &[
+    solution!(2019, 1, d01),
+    solution!(2019, 2, d02),
+    solution!(2019, 4, d04, str),
+];
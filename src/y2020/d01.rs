@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use anyhow::{anyhow, Context, Result};
 
 use crate::io;
+use crate::solution::Solution;
 
 const TARGET: i32 = 2020;
 
@@ -36,9 +37,26 @@ pub fn solve_b(nums: &[i32]) -> Option<i32> {
 }
 
 pub fn solve(input: &str) -> Result<(i32, i32)> {
-    let lines = io::parse_entries(input, '\n').context("Cannot parse input")?;
-    let a = solve_a(&lines).ok_or_else(|| anyhow!("No solution found for first subtask"))?;
-    let b = solve_b(&lines).ok_or_else(|| anyhow!("No solution found for second subtask"))?;
+    let nums = Day::parse(input)?;
+    Ok((Day::part1(&nums)?, Day::part2(&nums)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<i32>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        io::parse_entries(input, '\n').context("Cannot parse input")
+    }
+
+    fn part1(nums: &Self::Input) -> Result<i32> {
+        solve_a(nums).ok_or_else(|| anyhow!("No solution found for first subtask"))
+    }
 
-    Ok((a, b))
+    fn part2(nums: &Self::Input) -> Result<i32> {
+        solve_b(nums).ok_or_else(|| anyhow!("No solution found for second subtask"))
+    }
 }
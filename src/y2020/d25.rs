@@ -1,40 +1,38 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 
 use crate::io;
+use crate::number_theory::{discrete_log, mod_pow};
+use crate::solution::{Solution, Unsolved};
 
 const MOD: i64 = 20201227;
 
-fn mod_mul(a: i64, b: i64) -> i64 {
-    (a * b) % MOD
+pub fn solve(input: &str) -> Result<i64> {
+    Day::part1(&Day::parse(input)?)
 }
 
-fn mod_pow(mut a: i64, mut b: i64) -> i64 {
-    let mut res = 1;
-    while b > 0 {
-        if b & 1 > 0 {
-            res = mod_mul(res, a);
+/// There is no second subtask, its star is awarded for all the others.
+pub struct Day;
+
+impl Solution for Day {
+    /// The public keys of the card and the door.
+    type Input = (i64, i64);
+    type Part1 = i64;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        match io::parse_entries(input, '\n')?[..] {
+            [a, b] => Ok((a, b)),
+            _ => bail!("Expected two numbers as input"),
         }
-        a = mod_mul(a, a);
-        b /= 2;
     }
-    res
-}
 
-pub fn solve(input: &str) -> Result<i64> {
-    let (a, b) = match io::parse_entries(input, '\n')?[..] {
-        [a, b] => (a, b),
-        _ => bail!("Expected two numbers as input"),
-    };
-
-    let mut val = 1;
-    let mut log = 1;
-    for i in 0.. {
-        if val == a {
-            log = i;
-            break;
-        }
-        val = mod_mul(val, 7);
+    fn part1(&(a, b): &Self::Input) -> Result<i64> {
+        let log = discrete_log(7, a, MOD).ok_or_else(|| anyhow!("{} is not a power of 7", a))?;
+
+        mod_pow(b, log, MOD).ok_or_else(|| anyhow!("Invalid modulus {}", MOD))
     }
 
-    Ok(mod_pow(b, log))
+    fn part2(_: &Self::Input) -> Result<Unsolved> {
+        Ok(Unsolved)
+    }
 }
@@ -1,15 +1,15 @@
 use anyhow::{anyhow, Result};
 
 use crate::io;
+use crate::solution::Solution;
 
 type Num = u32;
 
 fn parse_line(line: &str) -> Num {
-    line.chars()
-        .fold(0, |acc, c| match c {
-            '0'..='9' => 2 * acc + (c as u32 - '0' as u32) as Num,
-            _ => panic!("Invalid char {}", c),
-        })
+    line.chars().fold(0, |acc, c| match c {
+        '0'..='9' => 2 * acc + (c as u32 - '0' as u32) as Num,
+        _ => panic!("Invalid char {}", c),
+    })
 }
 
 fn parse(input: &str) -> (Vec<Num>, usize) {
@@ -73,10 +73,27 @@ pub fn solve_a(nums: &[Num], nr_bits: usize) -> usize {
 }
 
 pub fn solve(input: &str) -> Result<(usize, u64)> {
-    let (mut nums, nr_bits) = parse(input);
+    let parsed = Day::parse(input)?;
+    Ok((Day::part1(&parsed)?, Day::part2(&parsed)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    /// The numbers and their number of bits.
+    type Input = (Vec<Num>, usize);
+    type Part1 = usize;
+    type Part2 = u64;
 
-    let power = solve_a(&nums, nr_bits);
-    let life = solve_b(&mut nums, nr_bits)?;
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input))
+    }
+
+    fn part1((nums, nr_bits): &Self::Input) -> Result<usize> {
+        Ok(solve_a(nums, *nr_bits))
+    }
 
-    Ok((power, life))
+    fn part2((nums, nr_bits): &Self::Input) -> Result<u64> {
+        solve_b(&mut nums.clone(), *nr_bits)
+    }
 }
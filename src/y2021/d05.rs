@@ -2,6 +2,8 @@ use std::iter::successors;
 
 use anyhow::{anyhow, Result};
 
+use crate::solution::Solution;
+
 mod nom {
     pub use ::nom::{
         bytes::complete::tag, character::complete::i32, sequence::separated_pair, IResult,
@@ -45,13 +47,11 @@ fn is_axis_aligned(((sx, sy), (ex, ey)): &Line) -> bool {
     sx == ex || sy == ey
 }
 
-pub fn solve(input: &str) -> Result<(i32, i32)> {
-    let mut all_lines = parse(input)?;
-    let nr_axis_aligned = itertools::partition(all_lines.iter_mut(), is_axis_aligned);
-
+/// Number of points covered by at least two of the lines.
+fn count_overlaps(lines: &[Line]) -> i32 {
     let mut maxx = 0;
     let mut maxy = 0;
-    for ((sx, sy), (ex, ey)) in &all_lines {
+    for ((sx, sy), (ex, ey)) in lines {
         maxx = maxx.max(*sx);
         maxx = maxx.max(*ex);
         maxy = maxy.max(*sy);
@@ -60,22 +60,42 @@ pub fn solve(input: &str) -> Result<(i32, i32)> {
 
     let mut field = vec![vec![0_u8; maxy as usize + 1]; maxx as usize + 1];
     let mut total = 0;
-
-    let mut fill_in = |lines: &[Line]| {
-        for line in lines {
-            for (x, y) in range(*line) {
-                let cell = &mut field[x as usize][y as usize];
-                *cell += 1;
-                if *cell == 2 {
-                    total += 1;
-                }
+    for line in lines {
+        for (x, y) in range(*line) {
+            let cell = &mut field[x as usize][y as usize];
+            *cell += 1;
+            if *cell == 2 {
+                total += 1;
             }
         }
-        total
-    };
+    }
+    total
+}
 
-    Ok((
-        fill_in(&all_lines[..nr_axis_aligned]),
-        fill_in(&all_lines[nr_axis_aligned..]),
-    ))
+pub fn solve(input: &str) -> Result<(i32, i32)> {
+    let parsed = Day::parse(input)?;
+    Ok((Day::part1(&parsed)?, Day::part2(&parsed)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    /// All lines, with the axis aligned ones first, and their number.
+    type Input = (Vec<Line>, usize);
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut lines = parse(input)?;
+        let nr_axis_aligned = itertools::partition(lines.iter_mut(), is_axis_aligned);
+        Ok((lines, nr_axis_aligned))
+    }
+
+    fn part1((lines, nr_axis_aligned): &Self::Input) -> Result<i32> {
+        Ok(count_overlaps(&lines[..*nr_axis_aligned]))
+    }
+
+    fn part2((lines, _): &Self::Input) -> Result<i32> {
+        Ok(count_overlaps(lines))
+    }
 }
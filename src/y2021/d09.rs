@@ -1,8 +1,9 @@
-use std::io::BufRead;
+use std::io::{self, BufRead};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::union_find::UnionFind;
+use crate::grid::Grid;
+use crate::union_find::{SetIndex, UnionFind};
 
 type Data = (i32, u8);
 pub fn merger((size_a, min_a): Data, (size_b, min_b): Data) -> Data {
@@ -10,38 +11,31 @@ pub fn merger((size_a, min_a): Data, (size_b, min_b): Data) -> Data {
 }
 
 pub fn solve(buf: impl BufRead) -> Result<(i32, i32)> {
-    let mut uf = UnionFind::with_capacity(100);
-
-    let mut last = vec![];
-    let mut current = vec![];
-    for l in buf.split(b'\n') {
-        let l = l?;
-        if current.is_empty() {
-            current = vec![None; l.len()];
-            last = vec![None; l.len()];
-        }
+    let map = Grid::try_parse_with(&io::read_to_string(buf)?, |c| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        _ => bail!("Invalid height '{}'", c as char),
+    })?;
 
-        for (i, c) in l.into_iter().enumerate() {
-            let val = c - b'0';
-            current[i] = if val == 9 {
-                None
-            } else if i > 0 && current[i - 1].is_some() {
-                let idx = current[i - 1].unwrap();
-                let d = uf.get_mut(idx);
-                *d = merger(*d, (1, val));
-                Some(idx)
-            } else {
-                Some(uf.new_set((1, val)))
-            };
-
-            if let Some(mid) = current[i] {
-                if let Some(up) = last[i] {
-                    uf.merge_with(mid, up, merger);
-                }
+    let mut uf = UnionFind::with_capacity(100);
+    let mut sets: Grid<Option<SetIndex>> = Grid::new(map.width(), map.height(), None);
+
+    for ((x, y), &val) in map.iter() {
+        sets[(x, y)] = if val == 9 {
+            None
+        } else if x > 0 && sets[(x - 1, y)].is_some() {
+            let idx = sets[(x - 1, y)].unwrap();
+            let d = uf.get_mut(idx);
+            *d = merger(*d, (1, val));
+            Some(idx)
+        } else {
+            Some(uf.new_set((1, val)))
+        };
+
+        if let Some(mid) = sets[(x, y)] {
+            if let Some(&Some(up)) = y.checked_sub(1).and_then(|up| sets.get((x, up))) {
+                uf.merge_with(mid, up, merger);
             }
         }
-
-        std::mem::swap(&mut last, &mut current);
     }
 
     let mut basins: Vec<_> = uf.into_iter().map(|e| e.1).collect();
@@ -1,91 +1,50 @@
-use std::io::BufRead;
+use std::io::{self, BufRead};
 
-use anyhow::{Context, Result};
-use itertools::Itertools;
+use anyhow::{bail, Result};
 
-pub fn solve(buf: impl BufRead) -> Result<(usize, usize)> {
-    let mut energy: Vec<Vec<u8>> = buf
-        .split(b'\n')
-        .map_ok(|line| line.iter().map(|c| c - b'0').collect())
-        .collect::<std::io::Result<Vec<Vec<u8>>>>()
-        .context("Cannot parse input")?;
+use crate::automaton::{Automaton, Edges, Neighbourhood};
+use crate::grid::Grid;
 
-    let height = energy.len();
-    let width = energy[0].len();
-    let squids = height * width;
+/// Energy level of an octopus that has already flashed during this step.
+const FLASHED: u8 = u8::MAX;
 
-    let mut flashes = 0;
-    let mut flashes_after_100 = None;
-    let mut synchronized_step = None;
+/// Run a step and return the number of flashes.
+fn step(octopuses: &mut Automaton<u8>) -> usize {
+    octopuses.map(|&e| e + 1);
 
-    let mut flash_coords = Vec::with_capacity(squids);
+    // Flashes spread in waves until no octopus is charged anymore.
+    while octopuses.apply(&Neighbourhood::Moore, |&e, neighbours| match e {
+        10.. => FLASHED,
+        _ => e + neighbours.count(|&n| (10..FLASHED).contains(&n)) as u8,
+    }) > 0
+    {}
 
-    for step in 1.. {
-        flash_coords.clear();
-        let flashes_before_this_step = flashes;
+    // Only the octopuses that flashed change here.
+    octopuses.map(|&e| if e == FLASHED { 0 } else { e })
+}
 
-        for (y, line) in energy.iter_mut().enumerate() {
-            for (x, val) in line.iter_mut().enumerate() {
-                *val += 1;
-                if *val > 9 {
-                    *val = 50;
-                    flash_coords.push((y, x));
-                }
-            }
-        }
+pub fn solve(buf: impl BufRead) -> Result<(usize, usize)> {
+    let energy = Grid::try_parse_with(&io::read_to_string(buf)?, |c| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        _ => bail!("Invalid energy level '{}'", c as char),
+    })?;
 
-        for flash_idx in 0.. {
-            if flash_idx >= flash_coords.len() {
-                break;
-            }
+    let squids = energy.width() * energy.height();
+    let mut octopuses = Automaton::new(energy, Edges::Bounded);
 
-            let (y, x) = flash_coords[flash_idx];
-            if energy[y][x] >= 100 {
-                continue;
-            }
-            flashes += 1;
-            energy[y][x] = 100;
-            for (yp, line) in energy
-                .iter_mut()
-                .take(y + 2)
-                .enumerate()
-                .skip(y.saturating_sub(1))
-            {
-                for (xp, val) in line
-                    .iter_mut()
-                    .take(x + 2)
-                    .enumerate()
-                    .skip(x.saturating_sub(1))
-                {
-                    *val += 1;
-                    if *val > 9 && *val < 50 {
-                        *val = 50;
-                        flash_coords.push((yp, xp));
-                    }
-                }
-            }
-        }
+    let mut flashes_after_100 = 0;
+    let mut synchronized_step = None;
 
-        for line in energy.iter_mut() {
-            for val in line.iter_mut() {
-                if *val >= 100 {
-                    *val = 0;
-                }
-            }
-        }
+    while octopuses.generation() < 100 || synchronized_step.is_none() {
+        let flashes = octopuses.advance(step);
 
-        if flashes - flashes_before_this_step == squids {
-            synchronized_step = Some(step);
+        if octopuses.generation() <= 100 {
+            flashes_after_100 += flashes;
         }
-
-        if step == 100 {
-            flashes_after_100 = Some(flashes);
-        }
-
-        if synchronized_step.is_some() && flashes_after_100.is_some() {
-            break;
+        if flashes == squids && synchronized_step.is_none() {
+            synchronized_step = Some(octopuses.generation());
         }
     }
 
-    Ok((flashes_after_100.unwrap(), synchronized_step.unwrap()))
+    Ok((flashes_after_100, synchronized_step.unwrap()))
 }
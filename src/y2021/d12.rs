@@ -11,7 +11,7 @@ use combine::{
     Parser,
 };
 
-use crate::parse::combine_parse;
+use crate::{parse::combine_parse, solution::Solution};
 
 type RoomIndex = (bool, usize);
 type Visited = BitArray<Lsb0, [u8; 1]>;
@@ -43,40 +43,30 @@ impl Graph {
     }
 }
 
+/// Number of paths from `current` to one of the `ends`. Small rooms can
+/// only be visited once, except for a single one if `can_revisit` is set.
 fn rec(
     current: RoomIndex,
     graph: &Graph,
     ends: &HashSet<RoomIndex>,
     mut visited: Visited,
-    mut double: bool,
-) -> [i64; 2] {
-    let mut out = [0; 2];
-
+    mut can_revisit: bool,
+) -> i64 {
     if !current.0 {
         if visited[current.1] {
-            if double {
-                return out;
-            } else {
-                double = true;
+            if !can_revisit {
+                return 0;
             }
+            can_revisit = false;
         } else {
             visited.set(current.1, true);
         }
     }
 
-    if ends.contains(&current) {
-        out[1] += 1;
-        if !double {
-            out[0] += 1;
-        }
-    }
-
+    let mut out = ends.contains(&current) as i64;
     for e in graph.get(current) {
-        let [s, d] = rec(*e, graph, ends, visited, double);
-        out[0] += s;
-        out[1] += d;
+        out += rec(*e, graph, ends, visited, can_revisit);
     }
-
     out
 }
 
@@ -121,7 +111,24 @@ impl Interner {
     }
 }
 
-pub fn solve(buf: impl BufRead) -> Result<(i64, i64)> {
+/// The cave system, with the rooms next to the start and end.
+pub struct Caves {
+    graph: Graph,
+    starts: Vec<RoomIndex>,
+    ends: HashSet<RoomIndex>,
+}
+
+impl Caves {
+    fn nr_paths(&self, can_revisit: bool) -> i64 {
+        let visited = Visited::new([0]);
+        self.starts
+            .iter()
+            .map(|&room| rec(room, &self.graph, &self.ends, visited, can_revisit))
+            .sum()
+    }
+}
+
+fn parse(buf: impl BufRead) -> Result<Caves> {
     let mut ends = HashSet::new();
     let mut starts = vec![];
 
@@ -155,15 +162,35 @@ pub fn solve(buf: impl BufRead) -> Result<(i64, i64)> {
     if interner.nr_small_rooms > 8 {
         bail!("Too many small rooms: {}", interner.nr_small_rooms);
     }
-    let visited = Visited::new([0]);
-
-    let mut total_single = 0;
-    let mut total_double = 0;
-    for room in starts {
-        let [s, d] = rec(room, &graph, &ends, visited, false);
-        total_single += s;
-        total_double += d;
+
+    Ok(Caves {
+        graph,
+        starts,
+        ends,
+    })
+}
+
+pub fn solve(buf: impl BufRead) -> Result<(i64, i64)> {
+    let caves = parse(buf)?;
+    Ok((Day::part1(&caves)?, Day::part2(&caves)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Caves;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Caves> {
+        parse(input.as_bytes())
     }
 
-    Ok((total_single, total_double))
+    fn part1(caves: &Caves) -> Result<i64> {
+        Ok(caves.nr_paths(false))
+    }
+
+    fn part2(caves: &Caves) -> Result<i64> {
+        Ok(caves.nr_paths(true))
+    }
 }
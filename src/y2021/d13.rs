@@ -2,7 +2,20 @@ use std::{collections::HashSet, io::BufRead};
 
 use anyhow::{bail, Context, Result};
 
-pub fn solve(buf: impl BufRead) -> Result<usize> {
+use crate::grid::Grid;
+use crate::ocr;
+use crate::solution::Solution;
+use crate::visualise::{self, Frame};
+
+type Point = (i32, i32);
+
+/// The dots on the transparent paper, and the folds as axis and position.
+pub struct Paper {
+    points: HashSet<Point>,
+    folds: Vec<(u8, i32)>,
+}
+
+fn parse(buf: impl BufRead) -> Result<Paper> {
     let mut points = HashSet::new();
     let mut lines = buf.split(b'\n');
 
@@ -24,10 +37,12 @@ pub fn solve(buf: impl BufRead) -> Result<usize> {
         );
     }
 
-    let subtask = 1;
-
+    let mut folds = vec![];
     for line in lines {
         let line = line?;
+        if line.is_empty() {
+            continue;
+        }
         if line.len() < 14 {
             bail!(
                 "Fold instructon line too short: '{}'",
@@ -35,40 +50,76 @@ pub fn solve(buf: impl BufRead) -> Result<usize> {
             );
         }
         let axis = line[11];
+        if axis != b'x' && axis != b'y' {
+            bail!("Unexpected axis {}", axis as char);
+        }
         let pos: i32 = std::str::from_utf8(&line[13..])?.parse()?;
+        folds.push((axis, pos));
+    }
 
-        let mut folded = HashSet::new();
-        for &(x, y) in points.iter() {
-            let new_coords = match axis {
-                b'x' => (x - 2 * (x - pos).max(0), y),
-                b'y' => (x, y - 2 * (y - pos).max(0)),
-                _ => bail!("Unexpected axis {}", axis as char),
-            };
-            folded.insert(new_coords);
-        }
-        std::mem::swap(&mut folded, &mut points);
+    Ok(Paper { points, folds })
+}
 
-        if subtask == 1 {
-            break;
-        }
+fn fold(points: &HashSet<Point>, (axis, pos): (u8, i32)) -> HashSet<Point> {
+    points
+        .iter()
+        .map(|&(x, y)| match axis {
+            b'x' => (x - 2 * (x - pos).max(0), y),
+            _ => (x, y - 2 * (y - pos).max(0)),
+        })
+        .collect()
+}
+
+/// The paper after all the folds, cropped to the dots.
+fn fold_all(paper: &Paper) -> Grid<bool> {
+    let mut points = paper.points.clone();
+    for &instruction in &paper.folds {
+        points = fold(&points, instruction);
+        visualise::emit(|| Frame::points(points.iter().map(|&(x, y)| (x as i64, y as i64))));
     }
+    ocr::crop(points.iter().map(|&(x, y)| (x as i64, y as i64)))
+}
 
-    if subtask == 2 {
-        let (max_x, max_y) = points
-            .iter()
-            .fold((0, 0), |(lx, ly), (cx, cy)| (lx.max(*cx), ly.max(*cy)));
-
-        for y in 0..=max_y {
-            for x in 0..=max_x {
-                if points.contains(&(x, y)) {
-                    print!("#");
-                } else {
-                    print!(" ");
-                }
-            }
-            println!();
-        }
+pub fn solve(buf: impl BufRead) -> Result<(usize, String)> {
+    let paper = parse(buf)?;
+    Ok((Day::part1(&paper)?, Day::part2(&paper)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Paper;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Paper> {
+        parse(input.as_bytes())
+    }
+
+    fn part1(paper: &Paper) -> Result<usize> {
+        let first = *paper.folds.first().context("No fold instructions")?;
+        Ok(fold(&paper.points, first).len())
+    }
+
+    fn part2(paper: &Paper) -> Result<String> {
+        ocr::recognise(&fold_all(paper)).context("Cannot read the code on the folded paper")
     }
+}
 
-    Ok(points.len())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n\
+                     3,4\n3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5\n";
+        let paper = Day::parse(input).unwrap();
+        assert_eq!(Day::part1(&paper).unwrap(), 17);
+
+        // The example folds into a square instead of letters.
+        let folded = fold_all(&paper).render(|&dot| if dot { '#' } else { '.' });
+        assert_eq!(folded, "#####\n#...#\n#...#\n#...#\n#####\n");
+        assert!(Day::part2(&paper).is_err());
+    }
 }
@@ -3,6 +3,8 @@ use std::{collections::HashMap, io::BufRead};
 use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 
+use crate::solution::Solution;
+
 const NR_CHARS: usize = to_index(b'Z') + 1;
 
 const fn to_index(c: u8) -> usize {
@@ -33,7 +35,13 @@ fn get_quantity_diff(
     .ok_or_else(|| anyhow!("No items found in `counts` array."))
 }
 
-pub fn solve(buf: impl BufRead) -> Result<(u64, u64)> {
+/// The polymer template and the pair insertion rules, as letter indices.
+pub struct Polymer {
+    template: Vec<usize>,
+    rules: HashMap<(usize, usize), usize>,
+}
+
+fn parse(buf: impl BufRead) -> Result<Polymer> {
     let mut lines = buf.split(b'\n');
 
     let init = lines.next().ok_or_else(|| anyhow!("empty input!"))??;
@@ -51,20 +59,26 @@ pub fn solve(buf: impl BufRead) -> Result<(u64, u64)> {
         }
     }
 
-    let mut field_1 = [[0_u64; NR_CHARS]; NR_CHARS];
-    #[allow(unused_assignments)]
-    let mut field_2 = field_1;
+    let template = init.into_iter().map(to_index).collect();
+    Ok(Polymer { template, rules })
+}
 
-    for (a, b) in init.iter().copied().map(to_index).tuple_windows() {
+/// Apply the insertion rules `steps` times, and return the difference
+/// between the most and least common elements.
+fn quantity_diff_after(polymer: &Polymer, steps: usize) -> Result<u64> {
+    let Polymer { template, rules } = polymer;
+    let (&first, &last) = template
+        .first()
+        .zip(template.last())
+        .ok_or_else(|| anyhow!("empty polymer template!"))?;
+
+    let mut field_1 = [[0_u64; NR_CHARS]; NR_CHARS];
+    for (a, b) in template.iter().copied().tuple_windows() {
         field_1[a][b] += 1;
     }
 
-    let first = to_index(init[0]);
-    let last = to_index(init[init.len() - 1]);
-
-    let mut diff_10 = 0;
-    for i in 1..=40 {
-        field_2 = [[0; NR_CHARS]; NR_CHARS];
+    for _ in 0..steps {
+        let mut field_2 = [[0; NR_CHARS]; NR_CHARS];
         for (a, line) in field_1.iter().enumerate() {
             for (b, count) in line.iter().enumerate() {
                 if let Some(&c) = rules.get(&(a, b)) {
@@ -75,12 +89,33 @@ pub fn solve(buf: impl BufRead) -> Result<(u64, u64)> {
                 }
             }
         }
-        std::mem::swap(&mut field_1, &mut field_2);
+        field_1 = field_2;
+    }
 
-        if i == 10 {
-            diff_10 = get_quantity_diff(&field_1, first, last)?;
-        }
+    get_quantity_diff(&field_1, first, last)
+}
+
+pub fn solve(buf: impl BufRead) -> Result<(u64, u64)> {
+    let polymer = parse(buf)?;
+    Ok((Day::part1(&polymer)?, Day::part2(&polymer)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Polymer;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Polymer> {
+        parse(input.as_bytes())
     }
 
-    Ok((diff_10, get_quantity_diff(&field_1, first, last)?))
+    fn part1(polymer: &Polymer) -> Result<u64> {
+        quantity_diff_after(polymer, 10)
+    }
+
+    fn part2(polymer: &Polymer) -> Result<u64> {
+        quantity_diff_after(polymer, 40)
+    }
 }
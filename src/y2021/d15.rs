@@ -1,8 +1,13 @@
 use std::io::{self, BufRead};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use pathfinding::prelude::astar;
 
+use crate::{
+    grid::{Grid, Pos},
+    solution::Solution,
+};
+
 fn wrap(i: u8) -> u8 {
     match i {
         9 => 1,
@@ -10,63 +15,52 @@ fn wrap(i: u8) -> u8 {
     }
 }
 
+fn shortest_path(map: &Grid<u8>, target: Pos) -> Result<i32> {
+    astar(
+        &(0, 0),
+        |&pos| {
+            map.neighbours_4(pos)
+                .filter(|&(x, y)| x <= target.0 && y <= target.1)
+                .map(|n| (n, map[n] as i32))
+        },
+        |&(x, y)| (x as i32 - target.0 as i32).abs() + (y as i32 - target.1 as i32).abs(),
+        |&pos| pos == target,
+    )
+    .ok_or_else(|| anyhow!("No path to {:?} found", target))
+    .map(|(_, c)| c)
+}
+
 pub fn solve(buf: impl BufRead) -> Result<(i32, i32)> {
-    let mut map = buf.split(b'\n').collect::<io::Result<Vec<_>>>()?;
-    let height = map.len();
-    let width = map[0].len();
+    let tile = Day::parse(&io::read_to_string(buf)?)?;
+    Ok((Day::part1(&tile)?, Day::part2(&tile)?))
+}
 
-    for line in map.iter_mut() {
-        line.resize(width * 5, 0);
-        for v in line.iter_mut().take(width) {
-            *v -= b'0';
-        }
-    }
-    map.resize(height * 5, vec![0; width * 5]);
-    for line in map.iter_mut() {
-        for ix in 1..5 {
-            for x in 0..width {
-                line[ix * width + x] = wrap(line[(ix - 1) * width + x]);
-            }
-        }
-    }
-    for iy in 1..5 {
-        for y in 0..height {
-            for x in 0..(width * 5) {
-                map[iy * height + y][x] = wrap(map[(iy - 1) * height + y][x]);
-            }
-        }
-    }
+pub struct Day;
 
-    let solve = |target: (usize, usize)| -> Result<i32> {
-        astar(
-            &(0, 0),
-            |&(x, y)| {
-                let map = &map;
-                [(1, 0), (0, 1), (-1, 0), (0, -1)]
-                    .iter()
-                    .filter_map(move |(dx, dy)| {
-                        let y = y as i32 + dy;
-                        let x = x as i32 + dx;
+impl Solution for Day {
+    type Input = Grid<u8>;
+    type Part1 = i32;
+    type Part2 = i32;
 
-                        if y >= 0 && y <= target.1 as i32 && x >= 0 && x <= target.0 as i32 {
-                            Some((
-                                (x as usize, y as usize),
-                                (map[y as usize][x as usize]) as i32,
-                            ))
-                        } else {
-                            None
-                        }
-                    })
-            },
-            |&(x, y)| (x as i32 - target.0 as i32).abs() + (y as i32 - target.1 as i32).abs(),
-            |&pos| pos == target,
-        )
-        .ok_or_else(|| anyhow!("No path to {:?} found", target))
-        .map(|(_, c)| c)
-    };
+    fn parse(input: &str) -> Result<Grid<u8>> {
+        Grid::try_parse_with(input, |c| match c {
+            b'1'..=b'9' => Ok(c - b'0'),
+            _ => bail!("Invalid risk level '{}'", c as char),
+        })
+    }
 
-    Ok((
-        solve((width - 1, height - 1))?,
-        solve((width * 5 - 1, height * 5 - 1))?,
-    ))
+    fn part1(tile: &Grid<u8>) -> Result<i32> {
+        shortest_path(tile, (tile.width() - 1, tile.height() - 1))
+    }
+
+    /// The tile repeated five times in both directions, with the risk
+    /// increasing by one for every repetition.
+    fn part2(tile: &Grid<u8>) -> Result<i32> {
+        let (width, height) = (tile.width(), tile.height());
+        let map = Grid::from_fn(width * 5, height * 5, |(x, y)| {
+            let steps = x / width + y / height;
+            (0..steps).fold(tile[(x % width, y % height)], |v, _| wrap(v))
+        });
+        shortest_path(&map, (width * 5 - 1, height * 5 - 1))
+    }
 }
@@ -1,9 +1,20 @@
-use std::io::BufRead;
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, bail, Result};
 use bitvec::{order::Msb0, prelude::BitVec, slice::BitSlice};
+use combine::{
+    between, choice, eof, many1, parser,
+    parser::byte::{byte, digit, letter, spaces},
+    sep_by, ParseError, Parser, Stream,
+};
 use deku::prelude::*;
 
+use crate::parse::combine_parse;
+use crate::solution::Solution;
+
 type LiteralContainer = BitVec<Msb0, u64>;
 
 fn decode_literal(lit: &LiteralContainer) -> u64 {
@@ -11,16 +22,16 @@ fn decode_literal(lit: &LiteralContainer) -> u64 {
     contents >> (64 - lit.len())
 }
 
-#[derive(PartialEq, Debug, DekuRead)]
-struct Packet {
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+pub struct Packet {
     #[deku(bits = 3)]
-    pub version: u8,
+    version: u8,
 
     #[deku(bits = 3)]
-    pub packet_type: u8,
+    packet_type: u8,
 
     #[deku(ctx = "*packet_type")]
-    pub body: PacketBody,
+    body: PacketBody,
 }
 
 impl Packet {
@@ -69,17 +80,23 @@ impl Packet {
     }
 }
 
-#[derive(PartialEq, Debug, DekuRead)]
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
 #[deku(ctx = "packet_type: u8", id = "packet_type")]
 enum PacketBody {
     #[deku(id = "0b100")]
-    Literal(#[deku(reader = "literal_parser(deku::rest)")] LiteralContainer),
+    Literal(
+        #[deku(
+            reader = "literal_parser(deku::rest)",
+            writer = "literal_writer(deku::output, field_0)"
+        )]
+        LiteralContainer,
+    ),
 
     #[deku(id_pat = "_")]
     Operator(SubPackets),
 }
 
-#[derive(PartialEq, Debug, DekuRead)]
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
 #[deku(type = "bool", bits = "1")]
 enum SubPackets {
     #[deku(id = "false")]
@@ -127,6 +144,18 @@ fn literal_parser(
     Ok((&rest[consumed_bits..], out))
 }
 
+fn literal_writer(output: &mut BitVec<Msb0, u8>, lit: &LiteralContainer) -> Result<(), DekuError> {
+    let groups = lit.len() / 4;
+    for (i, group) in lit.chunks(4).enumerate() {
+        // All groups but the last are prefixed with a 1.
+        output.push(i + 1 < groups);
+        for bit in group.iter() {
+            output.push(*bit);
+        }
+    }
+    Ok(())
+}
+
 fn decode_hex(c: u8) -> Option<u8> {
     match c {
         b'0'..=b'9' => Some(c - b'0'),
@@ -135,26 +164,604 @@ fn decode_hex(c: u8) -> Option<u8> {
     }
 }
 
-pub fn solve(buf: impl BufRead) -> Result<(i32, u64)> {
-    let line = buf
-        .split(b'\n')
-        .next()
-        .and_then(Result::ok)
-        .ok_or_else(|| anyhow!("Cannot read first line of input!"))?;
-
-    let mut input: Vec<u8> = Vec::with_capacity((line.len() + 1) / 2);
-    for (i, hex) in line.into_iter().enumerate() {
-        let val = decode_hex(hex).ok_or_else(|| anyhow!("Invalid hex value: '{}'", hex))?;
-        if i % 2 == 0 {
-            input.push(val << 4);
+/// What went wrong while decoding a transmission.
+#[derive(Debug)]
+pub enum DecodeErrorKind {
+    /// Reading the input failed.
+    Io(io::Error),
+    /// A character that isn't an uppercase hex digit.
+    InvalidHex(u8),
+    /// The input ends in the middle of the given field.
+    Truncated(&'static str),
+    /// A literal with more than 64 bits.
+    LiteralOverflow,
+    /// The sub-packets of an operator run past the `length` bits given in
+    /// its header, which end at bit `end`.
+    LengthOverrun { length: usize, end: usize },
+    /// An operator with the wrong number of sub-packets.
+    SubPacketCount { packet_type: u8, count: usize },
+}
+
+/// A decoding error, with the bit offset where it was found and the indices
+/// of the sub-packets leading to the packet being decoded.
+#[derive(Debug)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub path: Vec<usize>,
+    pub kind: DecodeErrorKind,
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeErrorKind::Io(e) => write!(f, "Cannot read input: {}", e),
+            DecodeErrorKind::InvalidHex(c) => write!(f, "Invalid hex digit {:?}", *c as char),
+            DecodeErrorKind::Truncated(what) => write!(f, "Input ends inside the {}", what),
+            DecodeErrorKind::LiteralOverflow => write!(f, "Literal does not fit in 64 bits"),
+            DecodeErrorKind::LengthOverrun { length, end } => write!(
+                f,
+                "Sub-packets overrun their length of {} bits, which ends at bit {}",
+                length, end
+            ),
+            DecodeErrorKind::SubPacketCount { packet_type, count } => {
+                let name = OPERATORS
+                    .iter()
+                    .find(|(t, _)| t == packet_type)
+                    .map_or("?", |(_, n)| n);
+                if *packet_type >= 5 {
+                    write!(f, "{} needs two sub-packets, not {}", name, count)
+                } else {
+                    write!(f, "{} needs at least one sub-packet", name)
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bit {}, ", self.offset)?;
+        if self.path.is_empty() {
+            write!(f, "outermost packet")?;
         } else {
-            input[i / 2] += val;
+            let path: Vec<_> = self.path.iter().map(usize::to_string).collect();
+            write!(f, "sub-packet {}", path.join("."))?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            DecodeErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes packets straight from the hex digits of a reader, so the
+/// transmission never has to be in memory at once. It ends at the first
+/// whitespace or at the end of the input.
+struct Decoder<R> {
+    input: R,
+    nibble: u8,
+    bits_left: u32,
+    offset: usize,
+    path: Vec<usize>,
+}
+
+impl<R: BufRead> Decoder<R> {
+    fn new(input: R) -> Self {
+        Decoder {
+            input,
+            nibble: 0,
+            bits_left: 0,
+            offset: 0,
+            path: vec![],
+        }
+    }
+
+    fn error(&self, offset: usize, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError {
+            offset,
+            path: self.path.clone(),
+            kind,
+        }
+    }
+
+    fn bit(&mut self, what: &'static str) -> Result<bool, DecodeError> {
+        if self.bits_left == 0 {
+            let buf = match self.input.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) => return Err(self.error(self.offset, DecodeErrorKind::Io(e))),
+            };
+            let c = match buf.first() {
+                Some(c) if !c.is_ascii_whitespace() => *c,
+                _ => return Err(self.error(self.offset, DecodeErrorKind::Truncated(what))),
+            };
+            self.nibble = decode_hex(c)
+                .ok_or_else(|| self.error(self.offset, DecodeErrorKind::InvalidHex(c)))?;
+            self.input.consume(1);
+            self.bits_left = 4;
+        }
+        self.bits_left -= 1;
+        self.offset += 1;
+        Ok((self.nibble >> self.bits_left) & 1 == 1)
+    }
+
+    fn bits(&mut self, n: u32, what: &'static str) -> Result<u16, DecodeError> {
+        let mut value = 0;
+        for _ in 0..n {
+            value = (value << 1) | self.bit(what)? as u16;
+        }
+        Ok(value)
+    }
+
+    fn packet(&mut self) -> Result<Packet, DecodeError> {
+        let start = self.offset;
+        let version = self.bits(3, "version")? as u8;
+        let packet_type = self.bits(3, "packet type")? as u8;
+
+        if packet_type == 4 {
+            let mut lit = LiteralContainer::new();
+            loop {
+                let group = self.offset;
+                let more = self.bit("literal group")?;
+                if lit.len() == 64 {
+                    return Err(self.error(group, DecodeErrorKind::LiteralOverflow));
+                }
+                for _ in 0..4 {
+                    lit.push(self.bit("literal group")?);
+                }
+                if !more {
+                    break;
+                }
+            }
+            return Ok(Packet {
+                version,
+                packet_type,
+                body: PacketBody::Literal(lit),
+            });
+        }
+
+        // The other seven types are all operators.
+        let mut packets = vec![];
+        let body = if self.bit("length type")? {
+            let count = self.bits(11, "sub-packet count")?;
+            for _ in 0..count {
+                packets.push(self.sub_packet(packets.len())?);
+            }
+            SubPackets::CountDelimited { count, packets }
+        } else {
+            let length = self.bits(15, "sub-packet length")?;
+            let end = self.offset + length as usize;
+            while self.offset < end {
+                packets.push(self.sub_packet(packets.len())?);
+            }
+            if self.offset > end {
+                let kind = DecodeErrorKind::LengthOverrun {
+                    length: length as usize,
+                    end,
+                };
+                return Err(self.error(self.offset, kind));
+            }
+            SubPackets::LengthDelimited { length, packets }
+        };
+
+        let count = body.packets().len();
+        if count == 0 || (packet_type >= 5 && count != 2) {
+            let kind = DecodeErrorKind::SubPacketCount { packet_type, count };
+            return Err(self.error(start, kind));
+        }
+        Ok(Packet {
+            version,
+            packet_type,
+            body: PacketBody::Operator(body),
+        })
+    }
+
+    fn sub_packet(&mut self, index: usize) -> Result<Packet, DecodeError> {
+        self.path.push(index);
+        let packet = self.packet()?;
+        self.path.pop();
+        Ok(packet)
+    }
+}
+
+/// Decode the first packet of a hex transmission. Any padding after it is
+/// left unread.
+fn read_packet(input: impl BufRead) -> Result<Packet, DecodeError> {
+    Decoder::new(input).packet()
+}
+
+/// The operator packet types, with their names in expressions.
+const OPERATORS: [(u8, &str); 7] = [
+    (0, "sum"),
+    (1, "product"),
+    (2, "min"),
+    (3, "max"),
+    (5, "gt"),
+    (6, "lt"),
+    (7, "eq"),
+];
+
+/// How the sub-packets of an operator are delimited.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LengthType {
+    /// By their total length in bits, in a 15 bit field.
+    Bits,
+    /// By their number, in an 11 bit field.
+    Count,
+}
+
+/// An expression like `sum(1, product(2, 3))`.
+#[derive(PartialEq, Debug)]
+enum Expression {
+    Literal(u64),
+    Operator(String, Vec<Expression>),
+}
+
+parser! {
+    fn expression[Input]()(Input) -> Expression
+    where [
+        Input: Stream<Token = u8>,
+        Input::Error: ParseError<u8, Input::Range, Input::Position>,
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError:
+            From<::std::num::ParseIntError>,
+    ]
+    {
+        choice((
+            many1(digit()).and_then(|digits: Vec<u8>| {
+                String::from_utf8_lossy(&digits).parse().map(Expression::Literal)
+            }),
+            many1(letter())
+                .and(between(
+                    byte(b'(').skip(spaces()),
+                    byte(b')'),
+                    sep_by(expression().skip(spaces()), byte(b',').skip(spaces())),
+                ))
+                .map(|(name, args): (Vec<u8>, _)| {
+                    Expression::Operator(String::from_utf8_lossy(&name).into_owned(), args)
+                }),
+        ))
+    }
+}
+
+impl Packet {
+    fn literal(version: u8, value: u64) -> Packet {
+        // At least one group of 4 bits.
+        let groups = (64 - value.leading_zeros() as usize).div_ceil(4);
+        let mut lit = LiteralContainer::new();
+        for i in (0..4 * groups.max(1)).rev() {
+            lit.push((value >> i) & 1 == 1);
+        }
+        Packet {
+            version,
+            packet_type: 4,
+            body: PacketBody::Literal(lit),
+        }
+    }
+
+    fn operator(
+        version: u8,
+        packet_type: u8,
+        packets: Vec<Packet>,
+        length_type: LengthType,
+    ) -> Result<Packet> {
+        if packets.is_empty() {
+            bail!("An operator needs at least one sub-packet");
+        }
+        let packets = match length_type {
+            LengthType::Bits => {
+                let mut bits = 0;
+                for packet in &packets {
+                    bits += packet.to_bits()?.len();
+                }
+                match u16::try_from(bits) {
+                    Ok(length) if length < 1 << 15 => {
+                        SubPackets::LengthDelimited { length, packets }
+                    }
+                    _ => bail!("Sub-packets too long for the length field: {} bits", bits),
+                }
+            }
+            LengthType::Count => match u16::try_from(packets.len()) {
+                Ok(count) if count < 1 << 11 => SubPackets::CountDelimited { count, packets },
+                _ => bail!(
+                    "Too many sub-packets for the count field: {}",
+                    packets.len()
+                ),
+            },
+        };
+        Ok(Packet {
+            version,
+            packet_type,
+            body: PacketBody::Operator(packets),
+        })
+    }
+
+    /// Build the packets for an expression, with all versions 0.
+    fn from_expression(expr: &Expression, length_type: LengthType) -> Result<Packet> {
+        match expr {
+            Expression::Literal(value) => Ok(Packet::literal(0, *value)),
+            Expression::Operator(name, args) => {
+                let (packet_type, _) = OPERATORS
+                    .iter()
+                    .find(|(_, n)| n == name)
+                    .ok_or_else(|| anyhow!("Unknown operator: {}", name))?;
+                if *packet_type >= 5 && args.len() != 2 {
+                    bail!("{} needs two arguments, not {}", name, args.len());
+                }
+                let packets = args
+                    .iter()
+                    .map(|arg| Packet::from_expression(arg, length_type))
+                    .collect::<Result<_>>()?;
+                Packet::operator(0, *packet_type, packets, length_type)
+            }
+        }
+    }
+
+    fn to_hex(&self) -> Result<String> {
+        Ok(self
+            .to_bytes()?
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect())
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.body {
+            PacketBody::Literal(lit) => write!(f, "{}", decode_literal(lit)),
+            PacketBody::Operator(sub) => {
+                let name = OPERATORS
+                    .iter()
+                    .find(|(t, _)| *t == self.packet_type)
+                    .map_or("?", |(_, n)| n);
+                write!(f, "{}(", name)?;
+                for (i, packet) in sub.packets().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", packet)?;
+                }
+                write!(f, ")")
+            }
         }
     }
+}
+
+/// Encode an expression like `sum(1, product(2, 3))` as a hex transmission.
+pub fn encode(expression_str: &str, length_type: LengthType) -> Result<String> {
+    let expr = combine_parse(expression().skip(eof()), expression_str.trim().as_bytes())?;
+    Packet::from_expression(&expr, length_type)?.to_hex()
+}
+
+/// Decode a hex transmission into an expression.
+pub fn decode(hex: &str) -> Result<String> {
+    Ok(read_packet(hex.trim().as_bytes())?.to_string())
+}
+
+pub fn solve(buf: impl BufRead) -> Result<(i32, u64)> {
+    let packet = read_packet(buf)?;
+    Ok((Day::part1(&packet)?, Day::part2(&packet)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Packet;
+    type Part1 = i32;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Packet> {
+        Ok(read_packet(input.as_bytes())?)
+    }
+
+    fn part1(packet: &Packet) -> Result<i32> {
+        Ok(packet.version_sum())
+    }
+
+    fn part2(packet: &Packet) -> Result<u64> {
+        packet.eval()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
 
-    let packet = Packet::from_bytes((input.as_ref(), 0))
-        .context("Cannot decode packet")?
-        .1;
+    use anyhow::Context;
 
-    Ok((packet.version_sum(), packet.eval()?))
+    use super::*;
+
+    /// Decode a whole line with deku, to check [read_packet] against.
+    fn parse_hex(line: &[u8]) -> Result<Packet> {
+        let mut input: Vec<u8> = Vec::with_capacity((line.len() + 1) / 2);
+        for (i, &hex) in line.iter().enumerate() {
+            let val = decode_hex(hex).ok_or_else(|| anyhow!("Invalid hex value: '{}'", hex))?;
+            if i % 2 == 0 {
+                input.push(val << 4);
+            } else {
+                input[i / 2] += val;
+            }
+        }
+
+        let packet = Packet::from_bytes((input.as_ref(), 0))
+            .context("Cannot decode packet")?
+            .1;
+        Ok(packet)
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(decode("D2FE28").unwrap(), "2021");
+        assert_eq!(decode("C200B40A82").unwrap(), "sum(1, 2)");
+        assert_eq!(
+            decode("9C0141080250320F1802104A08").unwrap(),
+            "eq(sum(1, 3), product(2, 2))"
+        );
+        let packet = parse_hex(b"620080001611562C8802118E34").unwrap();
+        assert_eq!(packet.version_sum(), 12);
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(Packet::literal(6, 2021).to_hex().unwrap(), "D2FE28");
+        let lt = Packet::operator(
+            1,
+            6,
+            vec![Packet::literal(6, 10), Packet::literal(2, 20)],
+            LengthType::Bits,
+        );
+        assert_eq!(lt.unwrap().to_hex().unwrap(), "38006F45291200");
+        let max = Packet::operator(
+            7,
+            3,
+            vec![
+                Packet::literal(2, 1),
+                Packet::literal(4, 2),
+                Packet::literal(1, 3),
+            ],
+            LengthType::Count,
+        );
+        assert_eq!(max.unwrap().to_hex().unwrap(), "EE00D40C823060");
+
+        let expr = "eq(sum(1, 3), product(2, max(2, 0)))";
+        for length_type in [LengthType::Bits, LengthType::Count] {
+            let hex = encode(expr, length_type).unwrap();
+            assert_eq!(decode(&hex).unwrap(), expr);
+            assert_eq!(parse_hex(hex.as_bytes()).unwrap().eval().unwrap(), 1);
+        }
+        assert!(encode("sum(1, 2", LengthType::Bits).is_err());
+        assert!(encode("div(4, 2)", LengthType::Bits).is_err());
+        assert!(encode("gt(1, 2, 3)", LengthType::Bits).is_err());
+        assert!(encode("sum()", LengthType::Count).is_err());
+        assert!(encode("99999999999999999999", LengthType::Bits).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rand = crate::testing::lcg(16);
+
+        fn random_packet(rand: &mut impl FnMut(u64) -> u64, depth: u32) -> Packet {
+            let version = rand(8) as u8;
+            if depth == 0 || rand(3) == 0 {
+                let value = (rand(1 << 32) << 32 | rand(1 << 32)) >> rand(64);
+                return Packet::literal(version, value);
+            }
+            let (packet_type, _) = OPERATORS[rand(7) as usize];
+            let nr_packets = if packet_type >= 5 { 2 } else { 1 + rand(3) };
+            let packets = (0..nr_packets)
+                .map(|_| random_packet(rand, depth - 1))
+                .collect();
+            let length_type = [LengthType::Bits, LengthType::Count][rand(2) as usize];
+            Packet::operator(version, packet_type, packets, length_type).unwrap()
+        }
+
+        for _ in 0..200 {
+            let packet = random_packet(&mut rand, 4);
+            let hex = packet.to_hex().unwrap();
+            assert_eq!(parse_hex(hex.as_bytes()).unwrap(), packet, "{}", hex);
+            // One byte at a time, to exercise the streaming.
+            let reader = BufReader::with_capacity(1, hex.as_bytes());
+            assert_eq!(read_packet(reader).unwrap(), packet, "{}", hex);
+
+            let expr = packet.to_string();
+            for length_type in [LengthType::Bits, LengthType::Count] {
+                assert_eq!(decode(&encode(&expr, length_type).unwrap()).unwrap(), expr);
+            }
+        }
+    }
+
+    fn decode_error(hex: &str) -> (usize, Vec<usize>, DecodeErrorKind) {
+        let e = read_packet(hex.as_bytes()).unwrap_err();
+        (e.offset, e.path, e.kind)
+    }
+
+    #[test]
+    fn test_stream() {
+        let packet = read_packet(&b"9C0141080250320F1802104A08\nD2FE28"[..]).unwrap();
+        assert_eq!(packet.to_string(), "eq(sum(1, 3), product(2, 2))");
+        assert_eq!(packet.eval().unwrap(), 1);
+
+        // A long transmission, read a few digits at a time.
+        let literals = (0..2000).map(|n| Packet::literal(0, n)).collect();
+        let sum = Packet::operator(0, 0, literals, LengthType::Count).unwrap();
+        let hex = sum.to_hex().unwrap();
+        let reader = BufReader::with_capacity(16, hex.as_bytes());
+        let packet = read_packet(reader).unwrap();
+        assert_eq!(packet.eval().unwrap(), 1999 * 2000 / 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let (offset, path, kind) = decode_error("D2FE");
+        assert_eq!((offset, path), (16, vec![]));
+        assert!(matches!(kind, DecodeErrorKind::Truncated("literal group")));
+
+        // The second literal of sum(1, 2) is cut short.
+        let (offset, path, kind) = decode_error("C200B40A8");
+        assert_eq!((offset, path), (36, vec![1]));
+        assert!(matches!(kind, DecodeErrorKind::Truncated("literal group")));
+
+        let (offset, path, kind) = decode_error("C2x0");
+        assert_eq!((offset, path), (8, vec![]));
+        assert!(matches!(kind, DecodeErrorKind::InvalidHex(b'x')));
+
+        // A length of 5 bits, but the literal inside takes 11.
+        let packet = Packet {
+            version: 0,
+            packet_type: 0,
+            body: PacketBody::Operator(SubPackets::LengthDelimited {
+                length: 5,
+                packets: vec![Packet::literal(0, 1)],
+            }),
+        };
+        let (offset, path, kind) = decode_error(&packet.to_hex().unwrap());
+        assert_eq!((offset, path), (33, vec![]));
+        assert!(matches!(
+            kind,
+            DecodeErrorKind::LengthOverrun { length: 5, end: 27 }
+        ));
+
+        // gt(1, 2, 3), nested inside a max.
+        let gt = Packet {
+            version: 0,
+            packet_type: 5,
+            body: PacketBody::Operator(SubPackets::CountDelimited {
+                count: 3,
+                packets: (1..=3).map(|n| Packet::literal(0, n)).collect(),
+            }),
+        };
+        let max = Packet::operator(0, 3, vec![Packet::literal(0, 0), gt], LengthType::Count);
+        let (offset, path, kind) = decode_error(&max.unwrap().to_hex().unwrap());
+        assert_eq!((offset, path), (29, vec![1]));
+        assert!(matches!(
+            kind,
+            DecodeErrorKind::SubPacketCount {
+                packet_type: 5,
+                count: 3
+            }
+        ));
+
+        // 17 groups of 4 bits.
+        let mut lit = LiteralContainer::new();
+        lit.resize(68, true);
+        let packet = Packet {
+            version: 0,
+            packet_type: 4,
+            body: PacketBody::Literal(lit),
+        };
+        let (offset, path, kind) = decode_error(&packet.to_hex().unwrap());
+        assert_eq!((offset, path), (86, vec![]));
+        assert!(matches!(kind, DecodeErrorKind::LiteralOverflow));
+
+        let e = read_packet(&b"C200B40A8"[..]).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Bit 36, sub-packet 1: Input ends inside the literal group"
+        );
+    }
 }
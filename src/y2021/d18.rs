@@ -1,14 +1,15 @@
-use std::io::BufRead;
+use std::io::{self, BufRead};
 
 use anyhow::{Context, Result};
 use combine::{
-    between, choice,
+    between, choice, parser,
     parser::byte::{byte, digit},
     ParseError, Stream,
 };
 use itertools::Itertools;
 
 use crate::parse::combine_parse;
+use crate::solution::Solution;
 
 macro_rules! number {
     [$a: tt, $b: tt] => {
@@ -23,7 +24,7 @@ macro_rules! number {
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
-enum Number {
+pub enum Number {
     Regular(u8),
     Pair(Box<(Number, Number)>),
 }
@@ -76,7 +77,7 @@ impl Number {
             Number::Regular(n) => {
                 if *n >= 10 {
                     let l = *n / 2;
-                    let r = (*n + 1) / 2;
+                    let r = n.div_ceil(2);
                     *self = number![l, r];
                     true
                 } else {
@@ -123,29 +124,45 @@ parser! {
 }
 
 pub fn solve(buf: impl BufRead) -> Result<(i64, i64)> {
-    let numbers = buf
-        .split(b'\n')
-        .map(|r| combine_parse(number(), &*r?))
-        .collect::<Result<Vec<Number>>>()?;
-
-    let max = numbers
-        .iter()
-        .combinations(2)
-        .map(|v| {
-            let a = v[0].clone().add(v[1].clone()).magnitude();
-            let b = v[1].clone().add(v[0].clone()).magnitude();
-            a.max(b)
-        })
-        .max()
-        .context("At least 1 number in input expected")?;
-
-    let sum = numbers
-        .into_iter()
-        .fold1(Number::add)
-        .context("At least 1 number in input expected")?
-        .magnitude();
-
-    Ok((sum, max))
+    let numbers = Day::parse(&io::read_to_string(buf)?)?;
+    Ok((Day::part1(&numbers)?, Day::part2(&numbers)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<Number>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input
+            .lines()
+            .map(|line| combine_parse(number(), line.as_bytes()))
+            .collect()
+    }
+
+    fn part1(numbers: &Self::Input) -> Result<i64> {
+        Ok(numbers
+            .iter()
+            .cloned()
+            .fold1(Number::add)
+            .context("At least 1 number in input expected")?
+            .magnitude())
+    }
+
+    fn part2(numbers: &Self::Input) -> Result<i64> {
+        numbers
+            .iter()
+            .combinations(2)
+            .map(|v| {
+                let a = v[0].clone().add(v[1].clone()).magnitude();
+                let b = v[1].clone().add(v[0].clone()).magnitude();
+                a.max(b)
+            })
+            .max()
+            .context("At least 1 number in input expected")
+    }
 }
 
 #[cfg(test)]
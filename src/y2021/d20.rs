@@ -1,82 +1,65 @@
-use std::{io::BufRead, iter::repeat};
+use std::io::{self, BufRead};
 
 use anyhow::{Context, Result};
 
-type Map = Vec<Vec<bool>>;
-const PADDING: usize = 52;
+use crate::automaton::{step_sparse, Neighbourhood};
+use crate::grid::Grid;
+use crate::solution::Solution;
+use crate::sparse_grid::SparseGrid;
 
-#[allow(clippy::ptr_arg)]
-fn count(map: &Map) -> usize {
-    map.iter()
-        .map(|row| row.iter().filter(|b| **b).count())
-        .sum()
-}
-
-pub fn solve(buf: impl BufRead) -> Result<(usize, usize)> {
-    let mut lines = buf.split(b'\n');
-    let table_line = lines.next().context("Empty input")??;
-    let table: Vec<bool> = table_line.into_iter().map(|c| c == b'#').collect();
+/// The 3x3 square around a cell, in the order of the bits of the index.
+const SQUARE: [(i64, i64); 9] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (0, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
 
-    let mut map: Map = vec![];
-    let side_padding = [false; PADDING];
-    lines.next();
-    for line in lines {
-        let it = line?.into_iter().map(|c| c == b'#');
-        map.push(
-            side_padding
-                .into_iter()
-                .chain(it)
-                .chain(side_padding.into_iter())
-                .collect(),
-        );
-    }
+fn enhance(image: &SparseGrid<bool>, table: &[bool]) -> SparseGrid<bool> {
+    step_sparse(image, &Neighbourhood::Directional(&SQUARE), |_, square| {
+        table[square.iter().fold(0, |idx, &b| idx * 2 + b as usize)]
+    })
+}
 
-    let width = map[0].len();
-    let empty_line = vec![false; width];
-    map.splice(0..0, repeat(&empty_line).take(PADDING).cloned());
-    for _ in 0..PADDING {
-        map.push(empty_line.clone());
+/// The number of lit pixels after enhancing the image `steps` times.
+fn lit_after(table: &[bool], image: &SparseGrid<bool>, steps: usize) -> usize {
+    let mut image = image.clone();
+    for _ in 0..steps {
+        image = enhance(&image, table);
     }
-    let height = map.len();
+    image.count(|&b| b)
+}
 
-    let mut count_a = 0;
+pub fn solve(buf: impl BufRead) -> Result<(usize, usize)> {
+    let parsed = Day::parse(&io::read_to_string(buf)?)?;
+    Ok((Day::part1(&parsed)?, Day::part2(&parsed)?))
+}
 
-    let mut map2 = map.clone();
-    for i in 0..50 {
-        if i == 2 {
-            count_a = count(&map);
-        }
+pub struct Day;
 
-        for (y, row) in map2.iter_mut().enumerate().skip(1).take(height - 2) {
-            for (x, val) in row.iter_mut().enumerate().skip(1).take(width - 2) {
-                let idx = map[y - 1][x - 1] as usize * 256
-                    + map[y - 1][x] as usize * 128
-                    + map[y - 1][x + 1] as usize * 64
-                    + map[y][x - 1] as usize * 32
-                    + map[y][x] as usize * 16
-                    + map[y][x + 1] as usize * 8
-                    + map[y + 1][x - 1] as usize * 4
-                    + map[y + 1][x] as usize * 2
-                    + map[y + 1][x + 1] as usize;
+impl Solution for Day {
+    /// The enhancement table and the input image.
+    type Input = (Vec<bool>, SparseGrid<bool>);
+    type Part1 = usize;
+    type Part2 = usize;
 
-                *val = table[idx];
-            }
-        }
-        if table[0] {
-            let value = i % 2 == 0;
-            for j in 0..width {
-                map2[0][j] = value;
-                map2[height - 1][j] = value;
-            }
-            for row in map2.iter_mut() {
-                row[0] = value;
-                row[width - 1] = value;
-            }
-        }
+    fn parse(input: &str) -> Result<Self::Input> {
+        let (table_line, image) = input.split_once('\n').context("Empty input")?;
+        let table = table_line.bytes().map(|c| c == b'#').collect();
+        let image = SparseGrid::from_grid(&Grid::parse_with(image, |c| c == b'#')?, false);
+        Ok((table, image))
+    }
 
-        std::mem::swap(&mut map, &mut map2);
+    fn part1((table, image): &Self::Input) -> Result<usize> {
+        Ok(lit_after(table, image, 2))
     }
 
-    let count_b = count(&map);
-    Ok((count_a, count_b))
+    fn part2((table, image): &Self::Input) -> Result<usize> {
+        Ok(lit_after(table, image, 50))
+    }
 }
@@ -1,6 +1,8 @@
 use anyhow::{bail, Result};
 use itertools::Itertools;
 
+use crate::solution::Solution;
+
 fn solve_a(mut positions: [i32; 2]) -> u64 {
     let mut scores = [0u64; 2];
 
@@ -95,14 +97,35 @@ fn solve_b(positions: [i32; 2]) -> u64 {
 }
 
 pub fn solve(input: &str) -> Result<(u64, u64)> {
-    let positions = input
-        .lines()
-        .map(|line| Ok(line[28..].parse()?))
-        .collect::<Result<Vec<i32>>>()?;
+    let positions = Day::parse(input)?;
+    Ok((Day::part1(&positions)?, Day::part2(&positions)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    /// The starting positions of both players.
+    type Input = [i32; 2];
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let positions = input
+            .lines()
+            .map(|line| Ok(line[28..].parse()?))
+            .collect::<Result<Vec<i32>>>()?;
+
+        if positions.len() != 2 {
+            bail!("Expected exactly 2 lines!");
+        }
+        Ok([positions[0], positions[1]])
+    }
+
+    fn part1(&positions: &Self::Input) -> Result<u64> {
+        Ok(solve_a(positions))
+    }
 
-    if positions.len() != 2 {
-        bail!("Expected exactly 2 lines!");
+    fn part2(&positions: &Self::Input) -> Result<u64> {
+        Ok(solve_b(positions))
     }
-    let positions = [positions[0], positions[1]];
-    Ok((solve_a(positions), solve_b(positions)))
 }
@@ -10,11 +10,11 @@ use nom::{
     IResult,
 };
 
-use crate::octtree::Octtree;
+use crate::{octtree::Octtree, solution::Solution};
 
 // on x=-20..26,y=-36..17,z=-47..7
 #[derive(PartialEq, Eq, Debug)]
-struct Instruction {
+pub struct Instruction {
     fill: bool,
     bounds: [RangeInclusive<i64>; 3],
 }
@@ -75,10 +75,13 @@ fn parse(input: &str) -> Vec<Instruction> {
         .collect()
 }
 
-pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let instructions = parse(input);
+/// Number of cubes that are on after running the instructions.
+fn nr_filled(instructions: &[Instruction]) -> Result<i64> {
+    if instructions.is_empty() {
+        return Ok(0);
+    }
     let mut split_points = [vec![], vec![], vec![]];
-    for instruction in &instructions {
+    for instruction in instructions {
         for i in 0..3 {
             split_points[i].push(*instruction.bounds[i].start());
             split_points[i].push(*instruction.bounds[i].end() + 1);
@@ -91,17 +94,41 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
     }
     let mut tree = Octtree::new(split_points);
 
-    let mut result_a = None;
-
     for instruction in instructions {
-        if !instruction.is_small() && result_a.is_none() {
-            result_a = Some(tree.nr_filled());
-        }
-        tree.set_region(instruction.bounds, instruction.fill)
-            .unwrap();
+        tree.set_region(instruction.bounds.clone(), instruction.fill)?;
+    }
+    Ok(tree.nr_filled())
+}
+
+pub fn solve(input: &str) -> Result<(i64, i64)> {
+    let instructions = Day::parse(input)?;
+    Ok((Day::part1(&instructions)?, Day::part2(&instructions)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<Instruction>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Vec<Instruction>> {
+        Ok(parse(input))
+    }
+
+    /// The initialization procedure is the run of small instructions at the
+    /// start.
+    fn part1(instructions: &Self::Input) -> Result<i64> {
+        let nr_small = instructions
+            .iter()
+            .take_while(|instruction| instruction.is_small())
+            .count();
+        nr_filled(&instructions[..nr_small])
+    }
+
+    fn part2(instructions: &Self::Input) -> Result<i64> {
+        nr_filled(instructions)
     }
-    let result_b = tree.nr_filled();
-    Ok((result_a.unwrap_or(result_b), result_b))
 }
 
 #[cfg(test)]
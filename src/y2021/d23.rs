@@ -3,6 +3,8 @@ use pathfinding::prelude::astar;
 use std::fmt::Display;
 use std::io::BufRead;
 
+use crate::solution::Solution;
+
 type Cost = i64;
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
@@ -281,23 +283,37 @@ fn parse(buf: impl BufRead) -> Result<State<2>> {
     Ok(State { hallway, rooms })
 }
 
-pub fn solve(buf: impl BufRead) -> Result<(Cost, Cost)> {
-    let start = parse(buf)?;
+fn min_cost<const N: usize>(start: &State<N>) -> Result<Cost> {
     let target = State::target();
-
-    let cost_a = astar(&start, State::next, State::min_cost, |state| {
+    let (_, cost) = astar(start, State::next, State::min_cost, |state| {
         state == &target
     })
-    .ok_or_else(|| anyhow!("No path found"))?
-    .1;
+    .ok_or_else(|| anyhow!("No path found"))?;
+    Ok(cost)
+}
 
-    let full = start.extend();
-    let target_full = State::target();
-    let cost_b = astar(&full, State::next, State::min_cost, |state| {
-        state == &target_full
-    })
-    .ok_or_else(|| anyhow!("No path found"))?
-    .1;
+pub fn solve(buf: impl BufRead) -> Result<(Cost, Cost)> {
+    let start = parse(buf)?;
+    Ok((Day::part1(&start)?, Day::part2(&start)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = State<2>;
+    type Part1 = Cost;
+    type Part2 = Cost;
+
+    fn parse(input: &str) -> Result<State<2>> {
+        parse(input.as_bytes())
+    }
+
+    fn part1(start: &State<2>) -> Result<Cost> {
+        min_cost(start)
+    }
 
-    Ok((cost_a, cost_b))
+    /// The burrow with the two folded-out rows added to every room.
+    fn part2(start: &State<2>) -> Result<Cost> {
+        min_cost(&start.extend())
+    }
 }
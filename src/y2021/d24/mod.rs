@@ -0,0 +1,1473 @@
+mod sat;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{
+    io,
+    union_find::{SetIndex, UnionFind},
+};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+/// Address of a register
+#[repr(usize)]
+enum Register {
+    X = 0,
+    Y = 1,
+    Z = 2,
+    W = 3,
+}
+
+impl Register {
+    pub fn to_index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+/// Either a register address or a literal number
+enum Value {
+    Reg(Register),
+    Lit(i32),
+}
+
+/// The smallest and largest value an expression can take, assuming every
+/// input is a digit from 1 to 9.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+struct Bounds {
+    min: i64,
+    max: i64,
+}
+
+impl Bounds {
+    const DIGIT: Bounds = Bounds { min: 1, max: 9 };
+    const ANY: Bounds = Bounds {
+        min: i64::MIN,
+        max: i64::MAX,
+    };
+
+    fn exact(n: i64) -> Bounds {
+        Bounds { min: n, max: n }
+    }
+
+    /// The bounds of all the given values.
+    fn hull(values: impl IntoIterator<Item = i64>) -> Bounds {
+        values.into_iter().fold(
+            Bounds {
+                min: i64::MAX,
+                max: i64::MIN,
+            },
+            |b, n| Bounds {
+                min: b.min.min(n),
+                max: b.max.max(n),
+            },
+        )
+    }
+
+    /// Like [Bounds::hull], for values computed with checked arithmetic.
+    /// Since the ALU wraps around, an overflow can give any value.
+    fn checked_hull(values: impl IntoIterator<Item = Option<i64>>) -> Bounds {
+        values
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .map_or(Bounds::ANY, Bounds::hull)
+    }
+
+    fn within(self, min: i64, max: i64) -> bool {
+        min <= self.min && self.max <= max
+    }
+
+    fn is_disjoint(self, other: Bounds) -> bool {
+        self.max < other.min || other.max < self.min
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    /// A reference to an input number. Here we store the index of the input,
+    /// not the actual input value.
+    Input(u32),
+    /// A literal number value
+    Lit(i64),
+    Instr(Arc<Node>),
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Expression) -> bool {
+        match (self, other) {
+            (Expression::Input(a), Expression::Input(b)) => a == b,
+            (Expression::Lit(a), Expression::Lit(b)) => a == b,
+            (Expression::Instr(a), Expression::Instr(b)) => a.id == b.id,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Expression {}
+
+impl Hash for Expression {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Expression::Input(i) => (0, *i as i64).hash(state),
+            Expression::Lit(n) => (1, *n).hash(state),
+            Expression::Instr(node) => (2, node.id as i64).hash(state),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+enum Instruction<V> {
+    Inp(V),
+    Add(V, V),
+    Mul(V, V),
+    Div(V, V),
+    Mod(V, V),
+    Eql(V, V),
+}
+
+impl<T> Instruction<T> {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Instruction::Inp(_) => "",
+            Instruction::Add(_, _) => "+",
+            Instruction::Mul(_, _) => "*",
+            Instruction::Div(_, _) => "/",
+            Instruction::Mod(_, _) => "%",
+            Instruction::Eql(_, _) => "==",
+        }
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Instruction<U> {
+        match self {
+            Instruction::Inp(a) => Instruction::Inp(f(a)),
+            Instruction::Add(a, b) => Instruction::Add(f(a), f(b)),
+            Instruction::Mul(a, b) => Instruction::Mul(f(a), f(b)),
+            Instruction::Div(a, b) => Instruction::Div(f(a), f(b)),
+            Instruction::Mod(a, b) => Instruction::Mod(f(a), f(b)),
+            Instruction::Eql(a, b) => Instruction::Eql(f(a), f(b)),
+        }
+    }
+}
+
+impl Instruction<Value> {
+    /// The register the instruction writes to.
+    fn destination(&self) -> Register {
+        match self {
+            Instruction::Inp(reg)
+            | Instruction::Add(reg, _)
+            | Instruction::Mul(reg, _)
+            | Instruction::Div(reg, _)
+            | Instruction::Mod(reg, _)
+            | Instruction::Eql(reg, _) => assert_reg(*reg),
+        }
+    }
+}
+
+impl FromStr for Value {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let val = match s {
+            "x" => Value::Reg(Register::X),
+            "y" => Value::Reg(Register::Y),
+            "z" => Value::Reg(Register::Z),
+            "w" => Value::Reg(Register::W),
+            _ => {
+                let lit = s.parse::<i32>()?;
+                Value::Lit(lit)
+            }
+        };
+
+        Ok(val)
+    }
+}
+
+impl FromStr for Instruction<Value> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let (typ, values) = s.split_once(' ').unwrap_or((s, ""));
+        let values = io::parse_entries::<Value>(values, ' ')?;
+        if let Some(Value::Lit(lit)) = values.first() {
+            anyhow::bail!("instruction {typ} cannot write to the literal {lit}");
+        }
+
+        let one_value = match &values.as_slice() {
+            [v] => Ok(*v),
+            other => Err(anyhow::anyhow!(
+                "instruction {typ} expected one value, {} given",
+                other.len()
+            )),
+        };
+        let two_values = match &values.as_slice() {
+            [a, b] => Ok((*a, *b)),
+            other => Err(anyhow::anyhow!(
+                "instruction {typ} expected two values, {} given",
+                other.len()
+            )),
+        };
+
+        use Instruction::*;
+        let ins = match typ {
+            "inp" => Inp(one_value?),
+            "add" => {
+                let (a, b) = two_values?;
+                Add(a, b)
+            }
+            "mul" => {
+                let (a, b) = two_values?;
+                Mul(a, b)
+            }
+            "div" => {
+                let (a, b) = two_values?;
+                Div(a, b)
+            }
+            "mod" => {
+                let (a, b) = two_values?;
+                Mod(a, b)
+            }
+            "eql" => {
+                let (a, b) = two_values?;
+                Eql(a, b)
+            }
+            other => anyhow::bail!("unknown instruction {}", other),
+        };
+        Ok(ins)
+    }
+}
+
+/// Parsing already rejects literal destinations.
+fn assert_reg(val: Value) -> Register {
+    match val {
+        Value::Reg(register) => register,
+        Value::Lit(lit) => panic!("expected register, found literal {lit}"),
+    }
+}
+
+/// Source of the node ids, global so that ids of different rewriters never
+/// collide.
+static NEXT_NODE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A composed sub expression.
+#[derive(Debug)]
+struct Node {
+    /// Structurally equal nodes built by the same [Rewriter] share their id.
+    id: usize,
+    /// Note that the `Inp` instruction cannot be used in a sub expression.
+    instr: Instruction<Expression>,
+    bounds: Bounds,
+}
+
+/// Builds expressions, simplifying every new node with rewrite rules that
+/// use the bounds of its operands. Structurally equal nodes are hash-consed,
+/// so comparing and hashing expressions doesn't need to look into them.
+#[derive(Default)]
+struct Rewriter {
+    nodes: HashMap<Instruction<Expression>, Expression>,
+}
+
+impl Rewriter {
+    /// Wrap an instruction without simplifying it.
+    fn node(&mut self, instr: Instruction<Expression>) -> Expression {
+        if let Some(node) = self.nodes.get(&instr) {
+            return node.clone();
+        }
+        let bounds = match &instr {
+            Instruction::Inp(_) => panic!("inp cannot be part of an expression"),
+            Instruction::Add(a, b) => {
+                let (a, b) = (a.bounds(), b.bounds());
+                Bounds::checked_hull([a.min.checked_add(b.min), a.max.checked_add(b.max)])
+            }
+            Instruction::Mul(a, b) => {
+                let (a, b) = (a.bounds(), b.bounds());
+                Bounds::checked_hull([
+                    a.min.checked_mul(b.min),
+                    a.min.checked_mul(b.max),
+                    a.max.checked_mul(b.min),
+                    a.max.checked_mul(b.max),
+                ])
+            }
+            Instruction::Div(a, b) => {
+                let (a, b) = (a.bounds(), b.bounds());
+                if b.min > 0 || b.max < 0 {
+                    Bounds::checked_hull([
+                        a.min.checked_div(b.min),
+                        a.min.checked_div(b.max),
+                        a.max.checked_div(b.min),
+                        a.max.checked_div(b.max),
+                    ])
+                } else {
+                    Bounds::ANY
+                }
+            }
+            Instruction::Mod(a, b) => {
+                let (a, b) = (a.bounds(), b.bounds());
+                match (a.min >= 0, b.min > 0) {
+                    (true, true) => Bounds {
+                        min: 0,
+                        max: a.max.min(b.max - 1),
+                    },
+                    (false, true) => Bounds {
+                        min: -(b.max - 1),
+                        max: b.max - 1,
+                    },
+                    _ => Bounds::ANY,
+                }
+            }
+            Instruction::Eql(_, _) => Bounds { min: 0, max: 1 },
+        };
+        let node = Expression::Instr(Arc::new(Node {
+            id: NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed),
+            instr: instr.clone(),
+            bounds,
+        }));
+        self.nodes.insert(instr, node.clone());
+        node
+    }
+
+    /// Build the expression for an instruction, simplifying where possible.
+    fn apply(&mut self, instr: Instruction<Expression>) -> Expression {
+        match instr {
+            Instruction::Inp(_) => panic!("inp cannot be part of an expression"),
+            Instruction::Add(a, b) => self.sum(a, b),
+            Instruction::Mul(a, b) => self.product(a, b),
+            Instruction::Div(a, b) => self.quotient(a, b),
+            Instruction::Mod(a, b) => self.remainder(a, b),
+            Instruction::Eql(a, b) => self.equals(a, b),
+        }
+    }
+
+    fn sum(&mut self, a: Expression, b: Expression) -> Expression {
+        match (a.lit(), b.lit()) {
+            (Some(x), Some(y)) => Expression::Lit(x.wrapping_add(y)),
+            (Some(0), _) => b,
+            (_, Some(0)) => a,
+            // Keep literals on the right, and merge them.
+            (Some(_), None) => self.sum(b, a),
+            (None, Some(y)) => match a.split_literal() {
+                Some((a, x)) => self.sum(a, Expression::Lit(x.wrapping_add(y))),
+                None => self.node(Instruction::Add(a, b)),
+            },
+            (None, None) => self.node(Instruction::Add(a, b)),
+        }
+    }
+
+    fn product(&mut self, a: Expression, b: Expression) -> Expression {
+        match (a.lit(), b.lit()) {
+            (Some(x), Some(y)) => Expression::Lit(x.wrapping_mul(y)),
+            (Some(0), _) | (_, Some(0)) => Expression::Lit(0),
+            (Some(1), _) => b,
+            (_, Some(1)) => a,
+            (Some(_), None) => self.product(b, a),
+            (None, Some(y)) => match a.split_factor() {
+                Some((a, x)) => self.product(a, Expression::Lit(x.wrapping_mul(y))),
+                None => self.node(Instruction::Mul(a, b)),
+            },
+            (None, None) => self.node(Instruction::Mul(a, b)),
+        }
+    }
+
+    fn quotient(&mut self, a: Expression, b: Expression) -> Expression {
+        let (Some(d), non_negative) = (b.lit(), a.bounds().min >= 0) else {
+            return self.node(Instruction::Div(a, b));
+        };
+        if let Some(x) = a.lit().filter(|_| d != 0) {
+            return Expression::Lit(x.wrapping_div(d));
+        }
+        if d == 1 {
+            return a;
+        }
+        if d <= 0 {
+            return self.node(Instruction::Div(a, b));
+        }
+        if a.bounds().within(0, d - 1) {
+            return Expression::Lit(0);
+        }
+        if let Some((high, _)) = a.split_digit(d) {
+            return high;
+        }
+        // (x * k) / d = x * (k / d) if d divides k.
+        if let Some((x, k)) = a.split_factor().filter(|(_, k)| k % d == 0) {
+            return self.product(x, Expression::Lit(k / d));
+        }
+        if non_negative {
+            // (x / e) / d = x / (e * d), and (x + k) / d = x / d + k / d if
+            // d divides k. Both only hold without negative values, as the
+            // division rounds towards zero.
+            if let Some((x, e)) = a.split_divisor().filter(|(x, _)| x.bounds().min >= 0) {
+                if let Some(ed) = e.checked_mul(d) {
+                    return self.quotient(x, Expression::Lit(ed));
+                }
+            }
+            if let Some((x, k)) = a
+                .split_literal()
+                .filter(|(x, k)| *k >= 0 && k % d == 0 && x.bounds().min >= 0)
+            {
+                let x = self.quotient(x, b);
+                return self.sum(x, Expression::Lit(k / d));
+            }
+        }
+        self.node(Instruction::Div(a, b))
+    }
+
+    fn remainder(&mut self, a: Expression, b: Expression) -> Expression {
+        // A negative value or divisor is an error, so there's nothing to
+        // preserve.
+        let Some(d) = b.lit().filter(|&d| d > 0 && a.bounds().min >= 0) else {
+            return self.node(Instruction::Mod(a, b));
+        };
+        if let Some(x) = a.lit() {
+            return Expression::Lit(x % d);
+        }
+        if d == 1 {
+            return Expression::Lit(0);
+        }
+        if a.bounds().within(0, d - 1) {
+            return a;
+        }
+        if let Some((_, low)) = a.split_digit(d) {
+            return low;
+        }
+        if a.split_factor().is_some_and(|(_, k)| k % d == 0) {
+            return Expression::Lit(0);
+        }
+        // (x % e) % d = x % d if d divides e.
+        if let Some((x, _)) = a
+            .split_modulus()
+            .filter(|(x, e)| e % d == 0 && x.bounds().min >= 0)
+        {
+            return self.remainder(x, b);
+        }
+        // (x + k) % d = x % d if d divides k.
+        if let Some((x, _)) = a
+            .split_literal()
+            .filter(|(x, k)| *k >= 0 && k % d == 0 && x.bounds().min >= 0)
+        {
+            return self.remainder(x, b);
+        }
+        self.node(Instruction::Mod(a, b))
+    }
+
+    fn equals(&mut self, a: Expression, b: Expression) -> Expression {
+        match (a.lit(), b.lit()) {
+            (Some(x), Some(y)) => Expression::Lit((x == y) as i64),
+            _ if a.bounds().is_disjoint(b.bounds()) => Expression::Lit(0),
+            _ if a == b => Expression::Lit(1),
+            (Some(_), None) => self.equals(b, a),
+            // A comparison result compared to 1 is the comparison itself.
+            (None, Some(1)) if a.bounds().within(0, 1) => a,
+            // x + k == n is x == n - k.
+            (None, Some(n)) if a.split_literal().is_some() => {
+                let (x, k) = a.split_literal().unwrap();
+                self.equals(x, Expression::Lit(n.wrapping_sub(k)))
+            }
+            // x * k == n is x == n / k, as long as x * k doesn't overflow.
+            (None, Some(n)) => match a.split_factor() {
+                Some((x, k))
+                    if k != 0
+                        && x.bounds().min.checked_mul(k).is_some()
+                        && x.bounds().max.checked_mul(k).is_some() =>
+                {
+                    match n % k {
+                        0 => self.equals(x, Expression::Lit(n / k)),
+                        _ => Expression::Lit(0),
+                    }
+                }
+                _ => self.node(Instruction::Eql(a, b)),
+            },
+            (None, None) => self.node(Instruction::Eql(a, b)),
+        }
+    }
+}
+
+impl Expression {
+    fn bounds(&self) -> Bounds {
+        match self {
+            Expression::Input(_) => Bounds::DIGIT,
+            Expression::Lit(n) => Bounds::exact(*n),
+            Expression::Instr(node) => node.bounds,
+        }
+    }
+
+    fn lit(&self) -> Option<i64> {
+        match self {
+            Expression::Lit(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn instr(&self) -> Option<&Instruction<Expression>> {
+        match self {
+            Expression::Instr(node) => Some(&node.instr),
+            _ => None,
+        }
+    }
+
+    /// Split `a + n` into `a` and the literal `n`.
+    fn split_literal(&self) -> Option<(Expression, i64)> {
+        match self.instr()? {
+            Instruction::Add(a, Expression::Lit(n)) => Some((a.clone(), *n)),
+            _ => None,
+        }
+    }
+
+    /// Split `a * n` into `a` and the literal `n`.
+    fn split_factor(&self) -> Option<(Expression, i64)> {
+        match self.instr()? {
+            Instruction::Mul(a, Expression::Lit(n)) => Some((a.clone(), *n)),
+            _ => None,
+        }
+    }
+
+    /// Split `a / n` into `a` and the literal `n`.
+    fn split_divisor(&self) -> Option<(Expression, i64)> {
+        match self.instr()? {
+            Instruction::Div(a, Expression::Lit(n)) => Some((a.clone(), *n)),
+            _ => None,
+        }
+    }
+
+    /// Split `a % n` into `a` and the literal `n`.
+    fn split_modulus(&self) -> Option<(Expression, i64)> {
+        match self.instr()? {
+            Instruction::Mod(a, Expression::Lit(n)) => Some((a.clone(), *n)),
+            _ => None,
+        }
+    }
+
+    /// The `a` of `a * d`.
+    fn multiple_of(&self, d: i64) -> Option<Expression> {
+        self.split_factor().and_then(|(a, n)| (n == d).then_some(a))
+    }
+
+    /// Split `high * d + low` into `high` and `low`, if `high ≥ 0` and
+    /// `0 ≤ low < d`, so that `low` is the last digit in base `d`. This is how
+    /// MONAD programs use `z` as a stack.
+    fn split_digit(&self, d: i64) -> Option<(Expression, Expression)> {
+        let (high, low) = match self.multiple_of(d) {
+            Some(high) => (high, Expression::Lit(0)),
+            None => match self.instr()? {
+                Instruction::Add(a, b) => match (a.multiple_of(d), b.multiple_of(d)) {
+                    (Some(high), _) => (high, b.clone()),
+                    (_, Some(high)) => (high, a.clone()),
+                    _ => return None,
+                },
+                _ => return None,
+            },
+        };
+        (high.bounds().min >= 0 && low.bounds().within(0, d - 1)).then_some((high, low))
+    }
+
+    /// The value for the given inputs, `None` if a `div` or `mod` fails.
+    fn evaluate(&self, inputs: &[i64]) -> Option<i64> {
+        self.evaluate_cached(inputs, &mut HashMap::new())
+    }
+
+    /// [Expression::evaluate] with the values of shared nodes cached by id,
+    /// so that each node is evaluated once.
+    fn evaluate_cached(
+        &self,
+        inputs: &[i64],
+        cache: &mut HashMap<usize, Option<i64>>,
+    ) -> Option<i64> {
+        let node = match self {
+            Expression::Input(i) => return inputs.get(*i as usize).copied(),
+            Expression::Lit(n) => return Some(*n),
+            Expression::Instr(node) => node,
+        };
+        if let Some(&value) = cache.get(&node.id) {
+            return value;
+        }
+        let mut eval = |e: &Expression| e.evaluate_cached(inputs, cache);
+        let value = match &node.instr {
+            Instruction::Inp(_) => None,
+            Instruction::Add(a, b) => eval(a).zip(eval(b)).map(|(a, b)| a.wrapping_add(b)),
+            Instruction::Mul(a, b) => eval(a).zip(eval(b)).map(|(a, b)| a.wrapping_mul(b)),
+            Instruction::Div(a, b) => eval(a)
+                .zip(eval(b))
+                .and_then(|(a, b)| (b != 0).then(|| a.wrapping_div(b))),
+            Instruction::Mod(a, b) => eval(a)
+                .zip(eval(b))
+                .and_then(|(a, b)| (a >= 0 && b > 0).then(|| a % b)),
+            Instruction::Eql(a, b) => eval(a).zip(eval(b)).map(|(a, b)| (a == b) as i64),
+        };
+        cache.insert(node.id, value);
+        value
+    }
+
+    /// Collect the indices of all inputs the expression depends on.
+    fn inputs(&self, out: &mut Vec<u32>) {
+        match self {
+            Expression::Input(i) => out.push(*i),
+            Expression::Lit(_) => {}
+            Expression::Instr(node) => {
+                node.instr.map(|e| e.inputs(out));
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nested = |e: &Expression| match e {
+            Expression::Instr(..) => format!("({e})"),
+            _ => e.to_string(),
+        };
+        match self {
+            Expression::Input(i) => write!(f, "i{i}"),
+            Expression::Lit(n) => write!(f, "{n}"),
+            Expression::Instr(node) => match &node.instr {
+                Instruction::Inp(_) => unreachable!(),
+                Instruction::Add(a, Expression::Lit(n)) if *n < 0 => {
+                    write!(f, "{} - {}", nested(a), -n)
+                }
+                Instruction::Add(a, b)
+                | Instruction::Mul(a, b)
+                | Instruction::Div(a, b)
+                | Instruction::Mod(a, b)
+                | Instruction::Eql(a, b) => {
+                    write!(f, "{} {} {}", nested(a), node.instr.symbol(), nested(b))
+                }
+            },
+        }
+    }
+}
+
+fn operand(registers: &[Expression; 4], val: &Value) -> Expression {
+    match val {
+        Value::Reg(register) => registers[register.to_index()].clone(),
+        Value::Lit(lit) => Expression::Lit(*lit as i64),
+    }
+}
+
+fn run_symbolically(instructions: &[Instruction<Value>]) -> Expression {
+    let mut rewriter = Rewriter::default();
+    let mut registers: [Expression; 4] = std::array::from_fn(|_| Expression::Lit(0));
+    let mut input_pointer = 0;
+
+    for instr in instructions {
+        let value = match instr {
+            Instruction::Inp(_) => {
+                input_pointer += 1;
+                Expression::Input(input_pointer - 1)
+            }
+            _ => rewriter.apply(instr.map(|v| operand(&registers, v))),
+        };
+        registers[instr.destination().to_index()] = value;
+    }
+
+    // extract the expression from register z
+    let mut out = Expression::Lit(0);
+    std::mem::swap(&mut out, &mut registers[Register::Z.to_index()]);
+    out
+}
+
+/// Run the program on concrete inputs and return the final registers.
+fn execute(instructions: &[Instruction<Value>], inputs: &[i64]) -> Result<[i64; 4]> {
+    let mut registers = [0i64; 4];
+    let mut inputs = inputs.iter();
+
+    for (pc, instr) in instructions.iter().enumerate() {
+        let args = instr.map(|v| match v {
+            Value::Reg(register) => registers[register.to_index()],
+            Value::Lit(lit) => *lit as i64,
+        });
+        registers[instr.destination().to_index()] = match args {
+            Instruction::Inp(_) => match inputs.next() {
+                Some(&n) => n,
+                None => bail!("instruction {pc}: ran out of inputs"),
+            },
+            Instruction::Add(a, b) => a.wrapping_add(b),
+            Instruction::Mul(a, b) => a.wrapping_mul(b),
+            Instruction::Div(_, 0) => bail!("instruction {pc}: division by zero"),
+            Instruction::Div(a, b) => a.wrapping_div(b),
+            Instruction::Mod(a, b) if a < 0 || b <= 0 => {
+                bail!("instruction {pc}: {a} mod {b} is undefined")
+            }
+            Instruction::Mod(a, b) => a % b,
+            Instruction::Eql(a, b) => (a == b) as i64,
+        };
+    }
+    Ok(registers)
+}
+
+/// Which model numbers [check_simplification] tries.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+enum Sample {
+    /// Every combination of digits.
+    Exhaustive,
+    Random {
+        count: usize,
+        seed: u64,
+    },
+}
+
+/// Run model numbers through both [execute] and the simplified `z`
+/// expression, and fail on the first one where they disagree, or where the
+/// value is outside the computed bounds. Inputs the program itself rejects
+/// are skipped, as simplification may drop the failing operation. Returns
+/// the number of inputs checked.
+#[cfg(test)]
+fn check_simplification(instructions: &[Instruction<Value>], sample: Sample) -> Result<usize> {
+    let expr = run_symbolically(instructions);
+    let nr_inputs = instructions
+        .iter()
+        .filter(|instr| matches!(instr, Instruction::Inp(_)))
+        .count();
+
+    let mut checked = 0;
+    let mut check = |digits: &[i64]| -> Result<()> {
+        let Ok(registers) = execute(instructions, digits) else {
+            return Ok(());
+        };
+        let z = registers[Register::Z.to_index()];
+        let simplified = expr.evaluate(digits);
+        if simplified != Some(z) {
+            bail!("for inputs {digits:?}, z is {z}, but the simplified expression gives {simplified:?}");
+        }
+        let bounds = expr.bounds();
+        if !Bounds::exact(z).within(bounds.min, bounds.max) {
+            bail!("for inputs {digits:?}, z is {z}, outside of {bounds:?}");
+        }
+        checked += 1;
+        Ok(())
+    };
+
+    let mut digits = vec![1; nr_inputs];
+    match sample {
+        Sample::Exhaustive => loop {
+            check(&digits)?;
+            let Some(i) = digits.iter().rposition(|&d| d < 9) else {
+                break;
+            };
+            digits[i] += 1;
+            digits[i + 1..].fill(1);
+        },
+        Sample::Random { count, seed } => {
+            let mut rand = crate::testing::lcg(seed);
+            for _ in 0..count {
+                for d in digits.iter_mut() {
+                    *d = rand(9) as i64 + 1;
+                }
+                check(&digits)?;
+            }
+        }
+    }
+    Ok(checked)
+}
+
+/// An assumption about the outcome of an `eql` instruction.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Constraint {
+    lhs: Expression,
+    rhs: Expression,
+    equal: bool,
+}
+
+impl Constraint {
+    fn holds(&self, inputs: &[i64]) -> bool {
+        match (self.lhs.evaluate(inputs), self.rhs.evaluate(inputs)) {
+            (Some(a), Some(b)) => (a == b) == self.equal,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = if self.equal { "==" } else { "!=" };
+        write!(f, "{} {op} {}", self.lhs, self.rhs)
+    }
+}
+
+/// Give up if the program branches more often than this.
+const MAX_PATHS: usize = 1 << 16;
+
+struct Path {
+    pc: usize,
+    registers: [Expression; 4],
+    input_pointer: u32,
+    constraints: Vec<Constraint>,
+}
+
+/// Run the program symbolically, and fork whenever the bounds can't decide
+/// an `eql` instruction. Returns the number of inputs and the constraints of
+/// every path that ends with `z = 0`.
+fn accepting_paths(instructions: &[Instruction<Value>]) -> Result<(usize, Vec<Vec<Constraint>>)> {
+    let mut stack = vec![Path {
+        pc: 0,
+        registers: std::array::from_fn(|_| Expression::Lit(0)),
+        input_pointer: 0,
+        constraints: vec![],
+    }];
+    let mut rewriter = Rewriter::default();
+    let mut nr_paths = 1;
+    let mut nr_inputs = 0;
+    let mut out = vec![];
+
+    while let Some(mut path) = stack.pop() {
+        while let Some(instr) = instructions.get(path.pc) {
+            path.pc += 1;
+            let mut value = match instr {
+                Instruction::Inp(_) => {
+                    path.input_pointer += 1;
+                    Expression::Input(path.input_pointer - 1)
+                }
+                _ => rewriter.apply(instr.map(|v| operand(&path.registers, v))),
+            };
+            let dst = instr.destination().to_index();
+
+            if let Some(Instruction::Eql(lhs, rhs)) = value.instr().cloned() {
+                nr_paths += 1;
+                if nr_paths > MAX_PATHS {
+                    bail!("the program branches into more than {MAX_PATHS} paths");
+                }
+                let mut constraint = Constraint {
+                    lhs,
+                    rhs,
+                    equal: false,
+                };
+                let mut other = Path {
+                    pc: path.pc,
+                    registers: path.registers.clone(),
+                    input_pointer: path.input_pointer,
+                    constraints: path.constraints.clone(),
+                };
+                other.registers[dst] = Expression::Lit(0);
+                other.constraints.push(constraint.clone());
+                stack.push(other);
+
+                constraint.equal = true;
+                path.constraints.push(constraint);
+                value = Expression::Lit(1);
+            }
+            path.registers[dst] = value;
+        }
+
+        nr_inputs = nr_inputs.max(path.input_pointer as usize);
+        let z = &path.registers[Register::Z.to_index()];
+        match z {
+            Expression::Lit(0) => out.push(path.constraints),
+            _ if z.bounds().is_disjoint(Bounds::exact(0)) => {}
+            _ => bail!("cannot decide whether z = {z} is 0"),
+        }
+    }
+    Ok((nr_inputs, out))
+}
+
+/// Give up on groups of inputs that are constrained together if there are
+/// more than this many of them, as all their digits are tried.
+const MAX_GROUP: usize = 6;
+
+/// Model numbers are returned as `u64`, which has room for 19 digits.
+fn check_nr_inputs(nr_inputs: usize) -> Result<()> {
+    if nr_inputs > 19 {
+        bail!("{nr_inputs} digits don't fit into a u64");
+    }
+    Ok(())
+}
+
+/// The largest and smallest model number that satisfies all constraints.
+fn model_numbers(nr_inputs: usize, constraints: &[Constraint]) -> Result<Option<(u64, u64)>> {
+    check_nr_inputs(nr_inputs)?;
+
+    // Inputs that appear in a constraint together have to be chosen
+    // together, all others are independent.
+    let mut groups = UnionFind::with_sets(nr_inputs);
+    let mut per_root: HashMap<usize, Vec<&Constraint>> = HashMap::new();
+    for c in constraints {
+        let mut inputs = vec![];
+        c.lhs.inputs(&mut inputs);
+        c.rhs.inputs(&mut inputs);
+        for pair in inputs.windows(2) {
+            groups.union(
+                SetIndex::from(pair[0] as usize),
+                SetIndex::from(pair[1] as usize),
+            );
+        }
+        match inputs.first() {
+            Some(&i) => per_root.entry(i as usize).or_default().push(c),
+            None if !c.holds(&[]) => return Ok(None),
+            None => {}
+        }
+    }
+    let mut per_group: HashMap<usize, Vec<&Constraint>> = HashMap::new();
+    for (i, cs) in per_root {
+        let root = groups.find(SetIndex::from(i)).index();
+        per_group.entry(root).or_default().extend(cs);
+    }
+
+    let place = |i: usize| 10u64.pow((nr_inputs - 1 - i) as u32);
+    let (mut max, mut min) = (0, 0);
+    for root in 0..nr_inputs {
+        if groups.find(SetIndex::from(root)).index() != root {
+            continue;
+        }
+        let members: Vec<usize> = groups
+            .members(SetIndex::from(root))
+            .map(|i| i.index())
+            .collect();
+        if members.len() > MAX_GROUP {
+            bail!("{} inputs are constrained together", members.len());
+        }
+        let constraints = per_group.remove(&root).unwrap_or_default();
+
+        // Try all digits of the group, like an odometer.
+        let mut digits = vec![1; nr_inputs];
+        let mut best: Option<(u64, u64)> = None;
+        loop {
+            if constraints.iter().all(|c| c.holds(&digits)) {
+                let value = members.iter().map(|&i| digits[i] as u64 * place(i)).sum();
+                best = Some(match best {
+                    None => (value, value),
+                    Some((hi, lo)) => (hi.max(value), lo.min(value)),
+                });
+            }
+            let Some(&i) = members.iter().find(|&&i| digits[i] < 9) else {
+                break;
+            };
+            for &j in members.iter().take_while(|&&j| j != i) {
+                digits[j] = 1;
+            }
+            digits[i] += 1;
+        }
+        let Some((hi, lo)) = best else {
+            return Ok(None);
+        };
+        max += hi;
+        min += lo;
+    }
+    Ok(Some((max, min)))
+}
+
+fn print_expression(expr: Expression) {
+    // Count the uses of every node, to find the shared ones.
+    fn count_uses(expr: &Expression, uses: &mut HashMap<usize, usize>) {
+        if let Expression::Instr(node) = expr {
+            let count = uses.entry(node.id).or_default();
+            *count += 1;
+            if *count == 1 {
+                node.instr.map(|e| count_uses(e, uses));
+            }
+        }
+    }
+    let mut uses = HashMap::new();
+    count_uses(&expr, &mut uses);
+
+    let mut sub_expressions: HashMap<usize, (i32, String)> = HashMap::new();
+    let mut var_nr = 0;
+
+    fn inner(
+        expr: &Expression,
+        uses: &HashMap<usize, usize>,
+        sub_expressions: &mut HashMap<usize, (i32, String)>,
+        var_nr: &mut i32,
+    ) -> String {
+        match expr {
+            Expression::Input(i) => format!("i{i}"),
+            Expression::Lit(n) => format!("{n}"),
+            Expression::Instr(node) => {
+                let symbol = node.instr.symbol();
+                let (a, b) = match &node.instr {
+                    Instruction::Inp(_) => panic!(),
+                    Instruction::Add(a, b)
+                    | Instruction::Mul(a, b)
+                    | Instruction::Div(a, b)
+                    | Instruction::Mod(a, b)
+                    | Instruction::Eql(a, b) => (a, b),
+                };
+                let is_shared = uses[&node.id] > 1;
+                if is_shared {
+                    if !sub_expressions.contains_key(&node.id) {
+                        // generate a new subexpression
+                        let body = format!(
+                            "{} {symbol} {}",
+                            inner(a, uses, sub_expressions, var_nr),
+                            inner(b, uses, sub_expressions, var_nr)
+                        );
+
+                        let var = *var_nr;
+                        *var_nr += 1;
+
+                        sub_expressions.insert(node.id, (var, body));
+                    }
+
+                    let (var, _) = sub_expressions.get(&node.id).unwrap();
+                    format!("v{var}")
+                } else {
+                    format!(
+                        "({} {symbol} {})",
+                        inner(a, uses, sub_expressions, var_nr),
+                        inner(b, uses, sub_expressions, var_nr)
+                    )
+                }
+            }
+        }
+    }
+
+    let main_expression = inner(&expr, &uses, &mut sub_expressions, &mut var_nr);
+    let mut sub_expressions: Vec<_> = sub_expressions.into_values().collect();
+    sub_expressions.sort();
+
+    for (var, body) in sub_expressions {
+        println!("v{var} = {body}");
+    }
+
+    println!("{main_expression}");
+}
+
+fn parse_program(input: &str) -> Result<Vec<Instruction<Value>>> {
+    io::parse_entries(input, '\n')
+}
+
+/// Pretty-print the program after simplification. Useful to see what the
+/// solver is working with.
+#[allow(unused)]
+fn preprocess(input: &str) -> Result<()> {
+    let program = parse_program(input)?;
+    let expr = run_symbolically(&program);
+    print_expression(expr);
+    Ok(())
+}
+
+/// Follow every way the `eql` instructions can go, collect the constraints
+/// on the paths that end with `z = 0` (e.g. `i3 + 1 == i4`), and find the
+/// largest and smallest model numbers that satisfy them.
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let program = parse_program(input)?;
+    let (nr_inputs, paths) = accepting_paths(&program)?;
+
+    let mut best: Option<(u64, u64)> = None;
+    for constraints in &paths {
+        if let Some((max, min)) = model_numbers(nr_inputs, constraints)? {
+            best = Some(match best {
+                None => (max, min),
+                Some((hi, lo)) => (hi.max(max), lo.min(min)),
+            });
+        }
+    }
+    let (max, min) = best.ok_or_else(|| anyhow!("no model number is valid"))?;
+
+    // Make sure the analysis got it right.
+    verify(&program, max)?;
+    verify(&program, min)?;
+    Ok((max, min))
+}
+
+/// Run a model number that was found valid through the program, to catch
+/// mistakes in the analysis and failing `div` or `mod` instructions that
+/// don't end up in `z`.
+fn verify(program: &[Instruction<Value>], n: u64) -> Result<()> {
+    let digits: Vec<i64> = n.to_string().bytes().map(|b| (b - b'0') as i64).collect();
+    let z = execute(program, &digits)?[Register::Z.to_index()];
+    if z != 0 {
+        bail!("model number {n} was found valid, but ends with z = {z}");
+    }
+    Ok(())
+}
+
+/// The largest and smallest model numbers, found digit by digit with a SAT
+/// solver. Much slower than [solve], but it doesn't rely on the program
+/// being structured like MONAD.
+pub fn solve_with_sat(input: &str) -> Result<(u64, u64)> {
+    let program = parse_program(input)?;
+    let expr = run_symbolically(&program);
+    let nr_inputs = program
+        .iter()
+        .filter(|instr| matches!(instr, Instruction::Inp(_)))
+        .count();
+    check_nr_inputs(nr_inputs)?;
+
+    if sat::find_inputs(&expr, &vec![None; nr_inputs], 0)?.is_none() {
+        bail!("no model number is valid");
+    }
+    let extreme = |order: [i64; 9]| -> Result<u64> {
+        let mut fixed = vec![None; nr_inputs];
+        for i in 0..nr_inputs {
+            // There is a solution with the digits so far, so one of these
+            // has to work.
+            for digit in order {
+                fixed[i] = Some(digit);
+                if let Some(digits) = sat::find_inputs(&expr, &fixed, 0)? {
+                    // Keep the rest of the solution if it's already optimal.
+                    if digits[i..].iter().all(|&d| d == order[0]) {
+                        return Ok(digits.iter().fold(0, |acc, &d| acc * 10 + d as u64));
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(fixed.iter().fold(0, |acc, d| acc * 10 + d.unwrap() as u64))
+    };
+    let max = extreme([9, 8, 7, 6, 5, 4, 3, 2, 1])?;
+    let min = extreme([1, 2, 3, 4, 5, 6, 7, 8, 9])?;
+    verify(&program, max)?;
+    verify(&program, min)?;
+    Ok((max, min))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use Instruction::*;
+    use Register::*;
+    use Value::*;
+
+    const EX1: &str = r#"inp x
+                         mul x -1"#;
+    const EX2: &str = r#"inp z
+                         inp x
+                         mul z 3
+                         eql z x"#;
+
+    #[test]
+    fn test_parse_ex1() {
+        let program = parse_program(EX1).unwrap();
+        assert_eq!(program, vec![Inp(Reg(X)), Mul(Reg(X), Lit(-1))]);
+    }
+    #[test]
+    fn test_parse_ex2() {
+        let program = parse_program(EX2).unwrap();
+        assert_eq!(
+            program,
+            vec![
+                Inp(Reg(Z)),
+                Inp(Reg(X)),
+                Mul(Reg(Z), Lit(3)),
+                Eql(Reg(Z), Reg(X))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_program("add 3 x").is_err());
+        assert!(parse_program("inp 1").is_err());
+        assert!(parse_program("in").is_err());
+        assert!(parse_program("inp").is_err());
+        assert!(parse_program("add x").is_err());
+        assert!(solve("inp w\nmul 2 w").is_err());
+    }
+
+    #[test]
+    fn test_empty_program_evaluation() {
+        let expr = run_symbolically(&[]);
+        assert_eq!(expr, Expression::Lit(0));
+    }
+
+    /// A MONAD program with one block per `(div z, add x, add y)` triple.
+    fn monad(blocks: &[(i32, i32, i32)]) -> String {
+        blocks
+            .iter()
+            .map(|(div, x, y)| {
+                format!(
+                    "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {div}\nadd x {x}\neql x w\n\
+                     eql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\n\
+                     add y w\nadd y {y}\nmul y x\nadd z y\n"
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_constraints() {
+        let program = parse_program(&monad(&[
+            (1, 11, 14),
+            (1, 14, 6),
+            (26, -5, 8),
+            (1, 13, 13),
+            (26, -12, 8),
+            (26, -9, 7),
+        ]))
+        .unwrap();
+        let (nr_inputs, paths) = accepting_paths(&program).unwrap();
+        assert_eq!(nr_inputs, 6);
+        let constraints: Vec<Vec<String>> = paths
+            .iter()
+            .map(|path| path.iter().map(|c| c.to_string()).collect())
+            .collect();
+        assert_eq!(
+            constraints,
+            [["i1 + 1 == i2", "i3 + 1 == i4", "i0 + 5 == i5"]]
+        );
+    }
+
+    #[test]
+    fn test_solve_monad() {
+        let blocks = [
+            (1, 12, 4),
+            (26, -7, 2),
+            (1, 15, 11),
+            (1, 10, 1),
+            (26, -3, 6),
+            (26, -14, 9),
+        ];
+        let input = monad(&blocks);
+        let program = parse_program(&input).unwrap();
+
+        // Compare against trying every model number.
+        let mut valid = vec![];
+        for n in 0..9i64.pow(6) {
+            let digits: Vec<i64> = (0..6).rev().map(|i| n / 9i64.pow(i) % 9 + 1).collect();
+            if execute(&program, &digits).unwrap()[Z.to_index()] == 0 {
+                valid.push(digits.iter().fold(0, |acc, d| acc * 10 + *d as u64));
+            }
+        }
+        let expected = (*valid.iter().max().unwrap(), *valid.iter().min().unwrap());
+        assert_eq!(solve(&input).unwrap(), expected);
+        assert_eq!(expected, (969976, 414311));
+
+        // An unsatisfiable block can't be popped.
+        assert!(solve(&monad(&[(1, 12, 4), (26, -20, 2)])).is_err());
+    }
+
+    #[test]
+    fn test_solve_small() {
+        // Valid if the first digit is two more than the second.
+        let input = "inp w\ninp x\nadd x 2\neql x w\neql x 0\nadd z x";
+        assert_eq!(solve(input).unwrap(), (97, 31));
+        assert!(solve("inp w\nadd z w").is_err());
+    }
+
+    #[test]
+    fn test_execute() {
+        assert_eq!(
+            execute(&parse_program(EX1).unwrap(), &[5]).unwrap(),
+            [-5, 0, 0, 0]
+        );
+        let program = parse_program(EX2).unwrap();
+        assert_eq!(execute(&program, &[2, 6]).unwrap(), [6, 0, 1, 0]);
+        assert_eq!(execute(&program, &[2, 7]).unwrap()[Z.to_index()], 0);
+
+        let error = |input: &str, inputs: &[i64]| {
+            execute(&parse_program(input).unwrap(), inputs)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(error(EX2, &[1]), "instruction 1: ran out of inputs");
+        assert_eq!(
+            error("inp w\ndiv w x", &[1]),
+            "instruction 1: division by zero"
+        );
+        assert_eq!(
+            error("inp w\nmul w -1\nmod w 2", &[3]),
+            "instruction 2: -3 mod 2 is undefined"
+        );
+        assert_eq!(error("mod z 0", &[]), "instruction 0: 0 mod 0 is undefined");
+    }
+
+    #[test]
+    fn test_evaluate_shared() {
+        // Fibonacci numbers, where each step uses the previous two nodes, so
+        // the expression is a small DAG but a huge tree.
+        let step = "mul w 0\nadd w x\nadd x y\nmul y 0\nadd y w\n";
+        let input = format!("inp x\ninp y\n{}add z x", step.repeat(60));
+        let program = parse_program(&input).unwrap();
+        let z = execute(&program, &[1, 2]).unwrap()[Z.to_index()];
+        assert_eq!(run_symbolically(&program).evaluate(&[1, 2]), Some(z));
+    }
+
+    #[test]
+    fn test_check_monad() {
+        let program = parse_program(&monad(&[
+            (1, 12, 4),
+            (1, 15, 11),
+            (26, -3, 6),
+            (1, 10, 1),
+            (26, -7, 2),
+        ]))
+        .unwrap();
+        assert_eq!(
+            check_simplification(&program, Sample::Exhaustive).unwrap(),
+            9usize.pow(5)
+        );
+
+        // The blocks of the puzzle input.
+        let program = parse_program(&monad(&[
+            (1, 11, 14),
+            (1, 14, 6),
+            (1, 15, 6),
+            (1, 13, 13),
+            (26, -12, 8),
+            (1, 10, 8),
+            (26, -15, 7),
+            (1, 13, 10),
+            (1, 10, 8),
+            (26, -13, 12),
+            (26, -13, 10),
+            (26, -14, 8),
+            (26, -2, 8),
+            (26, -9, 7),
+        ]))
+        .unwrap();
+        let sample = Sample::Random {
+            count: 1000,
+            seed: 24,
+        };
+        assert_eq!(check_simplification(&program, sample).unwrap(), 1000);
+        assert_eq!(
+            solve(&monad(&[(1, 11, 14), (26, -9, 7)])).unwrap(),
+            (49, 16)
+        );
+    }
+
+    /// A program of random instructions, with small literals so that `div`
+    /// and `mod` mostly succeed.
+    fn random_program(rand: &mut impl FnMut(u64) -> u64, len: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..len {
+            let op = ["inp", "add", "mul", "div", "mod", "eql"][rand(6) as usize];
+            let reg = ["x", "y", "z", "w"][rand(4) as usize];
+            let arg = match rand(3) {
+                0 => ["x", "y", "z", "w"][rand(4) as usize].to_string(),
+                _ => (rand(30) as i64 - 3).to_string(),
+            };
+            match op {
+                "inp" => input += &format!("inp {reg}\n"),
+                _ => input += &format!("{op} {reg} {arg}\n"),
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn test_check_overflow() {
+        // i0 * 2⁶³ wraps around to 0 for even digits.
+        let shift = "mul x 1073741824\nmul x 1073741824\nmul x 8\n";
+        let input = format!("inp x\n{shift}eql x 0\nadd z x");
+        let program = parse_program(&input).unwrap();
+        assert_eq!(
+            check_simplification(&program, Sample::Exhaustive).unwrap(),
+            9
+        );
+        let input = format!("inp x\n{shift}eql x 0\neql x 0\nadd z x");
+        assert_eq!(solve(&input).unwrap(), (8, 2));
+
+        // i64::MIN / -1 wraps around as well.
+        let input = format!("inp w\neql w 1\nadd x 1\n{shift}add x w\ndiv x -1\nadd z x");
+        let program = parse_program(&input).unwrap();
+        assert_eq!(
+            check_simplification(&program, Sample::Exhaustive).unwrap(),
+            9
+        );
+    }
+
+    #[test]
+    fn test_check_random_programs() {
+        let mut rand = lcg(2021);
+        for _ in 0..200 {
+            let input = random_program(&mut rand, 20);
+            let program = parse_program(&input).unwrap();
+            let sample = Sample::Random {
+                count: 50,
+                seed: rand(1000),
+            };
+            if let Err(e) = check_simplification(&program, sample) {
+                panic!("{e}\n{input}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let program = parse_program(
+            "inp x\nmul x 6\ndiv x 3\ninp y\nadd y 26\nmod y 13\nadd z x\nadd z y\n\
+             div z 4\ndiv z 2\neql x 2\neql x 1",
+        )
+        .unwrap();
+        let mut rewriter = Rewriter::default();
+        let mut registers: [Expression; 4] = std::array::from_fn(|_| Expression::Lit(0));
+        let mut inputs = 0;
+        for instr in &program {
+            registers[instr.destination().to_index()] = match instr {
+                Inp(_) => {
+                    inputs += 1;
+                    Expression::Input(inputs - 1)
+                }
+                _ => rewriter.apply(instr.map(|v| operand(&registers, v))),
+            };
+        }
+        let [x, y, z, _] = registers.map(|e| e.to_string());
+        // (i0 * 6) / 3 is i0 * 2, and i0 * 2 == 2 turns into i0 == 1.
+        assert_eq!(x, "i0 == 1");
+        assert_eq!(y, "i1");
+        assert_eq!(z, "((i0 * 2) + i1) / 8");
+
+        // Structurally equal expressions are shared.
+        let a = rewriter.apply(Add(Expression::Input(0), Expression::Input(1)));
+        let b = rewriter.apply(Add(Expression::Input(0), Expression::Input(1)));
+        assert_eq!(a, b);
+        assert_eq!(rewriter.apply(Eql(a, b)), Expression::Lit(1));
+    }
+
+    #[test]
+    fn test_solve_with_sat() {
+        let input = "inp w\ninp x\nadd x 2\neql x w\neql x 0\nadd z x";
+        assert_eq!(solve_with_sat(input).unwrap(), (97, 31));
+
+        let input = monad(&[(1, 12, 4), (1, 15, 11), (26, -3, 6), (26, -7, 2)]);
+        assert_eq!(solve_with_sat(&input).unwrap(), solve(&input).unwrap());
+        assert!(solve_with_sat(&monad(&[(1, 12, 4), (26, -20, 2)])).is_err());
+
+        // The mod fails for the digit 1, without affecting z.
+        assert!(solve_with_sat("inp w\nadd x w\nadd x -2\nmod x 3").is_err());
+        assert!(solve("inp w\nadd x w\nadd x -2\nmod x 3").is_err());
+        let input = "inp w\n".repeat(20);
+        assert!(solve_with_sat(&input).is_err());
+
+        // Finds inputs for other targets as well.
+        let expr = run_symbolically(&parse_program("inp z\ninp x\nmul z x\ndiv z 3").unwrap());
+        let digits = sat::find_inputs(&expr, &[Some(7), None], 16)
+            .unwrap()
+            .unwrap();
+        assert_eq!(digits, [7, 7]);
+        assert_eq!(sat::find_inputs(&expr, &[Some(1), None], 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_sat_random_programs() {
+        let mut rand = lcg(24);
+        let mut checked = 0;
+        while checked < 50 {
+            let input = random_program(&mut rand, 12);
+            let program = parse_program(&input).unwrap();
+            let nr_inputs = program.iter().filter(|i| matches!(i, Inp(_))).count();
+            if nr_inputs > 3 {
+                continue;
+            }
+            let expr = run_symbolically(&program);
+
+            // All values the expression takes.
+            let mut values = vec![];
+            for n in 0..9i64.pow(nr_inputs as u32) {
+                let digits: Vec<i64> = (0..nr_inputs as u32)
+                    .map(|i| n / 9i64.pow(i) % 9 + 1)
+                    .collect();
+                values.extend(expr.evaluate(&digits));
+            }
+            let none = vec![None; nr_inputs];
+            if let Some(&target) = values.get(rand(values.len().max(1) as u64) as usize) {
+                let found = sat::find_inputs(&expr, &none, target);
+                assert!(found.unwrap().is_some(), "{target}\n{input}");
+            }
+            let missing = values.iter().max().map_or(0, |n| n + 1);
+            let found = sat::find_inputs(&expr, &none, missing);
+            assert_eq!(found.unwrap(), None, "{missing}\n{input}");
+            checked += 1;
+        }
+    }
+}
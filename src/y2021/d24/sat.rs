@@ -0,0 +1,395 @@
+//! Translate ALU expressions into CNF, so that a SAT solver can find inputs
+//! for which an expression takes a given value, without knowing anything
+//! about the structure of the program.
+//!
+//! Every node becomes a word of bits in two's complement, wide enough for
+//! the bounds of all nodes, so no value wraps around. `div` and `mod` get
+//! fresh bits for the quotient `q` and remainder `r`, tied to the operands by
+//! `a = q * b + r`.
+
+use std::collections::HashMap;
+use std::ops::Not;
+
+use anyhow::{bail, Result};
+use rustsat::instances::{BasicVarManager, SatInstance};
+use rustsat::solvers::{Solve, SolverResult};
+use rustsat::types::{Lit, TernaryVal};
+
+use super::{Expression, Instruction};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Bit {
+    Const(bool),
+    Var(Lit),
+}
+
+impl Not for Bit {
+    type Output = Bit;
+
+    fn not(self) -> Bit {
+        match self {
+            Bit::Const(b) => Bit::Const(!b),
+            Bit::Var(l) => Bit::Var(!l),
+        }
+    }
+}
+
+type Word = Vec<Bit>;
+
+/// The number of bits needed for all values of the expression.
+fn width(expr: &Expression, target: i64) -> usize {
+    fn largest(expr: &Expression, seen: &mut HashMap<usize, u64>) -> u64 {
+        let own = {
+            let bounds = expr.bounds();
+            bounds.min.unsigned_abs().max(bounds.max.unsigned_abs())
+        };
+        match expr {
+            Expression::Input(_) | Expression::Lit(_) => own,
+            Expression::Instr(node) => {
+                if let Some(&n) = seen.get(&node.id) {
+                    return n;
+                }
+                let mut n = own;
+                node.instr.map(|e| n = n.max(largest(e, seen)));
+                seen.insert(node.id, n);
+                n
+            }
+        }
+    }
+    let largest = largest(expr, &mut HashMap::new()).max(target.unsigned_abs());
+    // One bit for the sign, and one to spare for sums of two values.
+    (64 - largest.leading_zeros() as usize + 2).clamp(6, 64)
+}
+
+struct Encoder {
+    instance: SatInstance<BasicVarManager>,
+    width: usize,
+    inputs: Vec<Word>,
+    nodes: HashMap<usize, Word>,
+}
+
+impl Encoder {
+    fn new(width: usize, nr_inputs: usize) -> Encoder {
+        let mut encoder = Encoder {
+            instance: SatInstance::new(),
+            width,
+            inputs: vec![],
+            nodes: HashMap::new(),
+        };
+        for _ in 0..nr_inputs {
+            let bits: Vec<Lit> = (0..4).map(|_| encoder.instance.new_lit()).collect();
+            // A digit from 1 to 9, so not 0 and not 10 (0b1010) or above.
+            encoder.instance.add_nary(&bits);
+            encoder.instance.add_binary(!bits[3], !bits[2]);
+            encoder.instance.add_binary(!bits[3], !bits[1]);
+
+            let mut word: Word = bits.into_iter().map(Bit::Var).collect();
+            word.resize(width, Bit::Const(false));
+            encoder.inputs.push(word);
+        }
+        encoder
+    }
+
+    fn fresh(&mut self) -> Lit {
+        self.instance.new_lit()
+    }
+
+    fn fresh_word(&mut self) -> Word {
+        (0..self.width).map(|_| Bit::Var(self.fresh())).collect()
+    }
+
+    fn constant(&self, n: i64) -> Word {
+        (0..self.width)
+            .map(|i| Bit::Const((n >> i.min(63)) & 1 == 1))
+            .collect()
+    }
+
+    fn assert(&mut self, bit: Bit) {
+        match bit {
+            Bit::Const(true) => {}
+            Bit::Const(false) => self.instance.add_nary(&[]),
+            Bit::Var(l) => self.instance.add_unit(l),
+        }
+    }
+
+    fn and(&mut self, a: Bit, b: Bit) -> Bit {
+        match (a, b) {
+            (Bit::Const(false), _) | (_, Bit::Const(false)) => Bit::Const(false),
+            (Bit::Const(true), x) | (x, Bit::Const(true)) => x,
+            _ if a == b => a,
+            _ if a == !b => Bit::Const(false),
+            (Bit::Var(a), Bit::Var(b)) => {
+                let out = self.fresh();
+                self.instance.add_binary(!out, a);
+                self.instance.add_binary(!out, b);
+                self.instance.add_ternary(out, !a, !b);
+                Bit::Var(out)
+            }
+        }
+    }
+
+    fn or(&mut self, a: Bit, b: Bit) -> Bit {
+        !self.and(!a, !b)
+    }
+
+    fn xor(&mut self, a: Bit, b: Bit) -> Bit {
+        match (a, b) {
+            (Bit::Const(c), x) | (x, Bit::Const(c)) => {
+                if c {
+                    !x
+                } else {
+                    x
+                }
+            }
+            _ if a == b => Bit::Const(false),
+            _ if a == !b => Bit::Const(true),
+            (Bit::Var(a), Bit::Var(b)) => {
+                let out = self.fresh();
+                self.instance.add_ternary(!out, a, b);
+                self.instance.add_ternary(!out, !a, !b);
+                self.instance.add_ternary(out, !a, b);
+                self.instance.add_ternary(out, a, !b);
+                Bit::Var(out)
+            }
+        }
+    }
+
+    fn and_all(&mut self, bits: &[Bit]) -> Bit {
+        let mut lits = vec![];
+        for &bit in bits {
+            match bit {
+                Bit::Const(false) => return Bit::Const(false),
+                Bit::Const(true) => {}
+                Bit::Var(l) => lits.push(l),
+            }
+        }
+        match lits[..] {
+            [] => Bit::Const(true),
+            [l] => Bit::Var(l),
+            _ => {
+                let out = self.fresh();
+                for &l in &lits {
+                    self.instance.add_binary(!out, l);
+                }
+                let mut clause: Vec<Lit> = lits.iter().map(|&l| !l).collect();
+                clause.push(out);
+                self.instance.add_nary(&clause);
+                Bit::Var(out)
+            }
+        }
+    }
+
+    /// `t` if `s` is set, else `f`.
+    fn select(&mut self, s: Bit, t: &[Bit], f: &[Bit]) -> Word {
+        t.iter()
+            .zip(f)
+            .map(|(&t, &f)| {
+                let t = self.and(s, t);
+                let f = self.and(!s, f);
+                self.or(t, f)
+            })
+            .collect()
+    }
+
+    fn add_with_carry(&mut self, a: &[Bit], b: &[Bit], mut carry: Bit) -> Word {
+        let mut out = Vec::with_capacity(a.len());
+        for (&a, &b) in a.iter().zip(b) {
+            let half = self.xor(a, b);
+            out.push(self.xor(half, carry));
+            let both = self.and(a, b);
+            let carried = self.and(half, carry);
+            carry = self.or(both, carried);
+        }
+        out
+    }
+
+    fn add(&mut self, a: &[Bit], b: &[Bit]) -> Word {
+        self.add_with_carry(a, b, Bit::Const(false))
+    }
+
+    fn sub(&mut self, a: &[Bit], b: &[Bit]) -> Word {
+        let not_b: Word = b.iter().map(|&bit| !bit).collect();
+        self.add_with_carry(a, &not_b, Bit::Const(true))
+    }
+
+    fn abs(&mut self, a: &[Bit]) -> Word {
+        let negated = self.sub(&self.constant(0), a);
+        self.select(a[a.len() - 1], &negated, a)
+    }
+
+    /// Shift and add, modulo `2^width`, which is also right for negative
+    /// numbers in two's complement.
+    fn mul(&mut self, a: &[Bit], b: &[Bit]) -> Word {
+        let mut out = self.constant(0);
+        for (shift, &bit) in b.iter().enumerate() {
+            if bit == Bit::Const(false) {
+                continue;
+            }
+            let mut partial = vec![Bit::Const(false); shift];
+            for &x in &a[..a.len() - shift] {
+                partial.push(self.and(x, bit));
+            }
+            out = self.add(&out, &partial);
+        }
+        out
+    }
+
+    fn equal(&mut self, a: &[Bit], b: &[Bit]) -> Bit {
+        let same: Vec<Bit> = a.iter().zip(b).map(|(&a, &b)| !self.xor(a, b)).collect();
+        self.and_all(&same)
+    }
+
+    /// Signed `a < b`, the sign of `a - b` computed with one more bit.
+    fn less_than(&mut self, a: &[Bit], b: &[Bit]) -> Bit {
+        let extend = |word: &[Bit]| {
+            let mut word = word.to_vec();
+            word.push(word[word.len() - 1]);
+            word
+        };
+        let difference = self.sub(&extend(a), &extend(b));
+        difference[difference.len() - 1]
+    }
+
+    /// Fresh words `(q, r)` with `a = q * b + r`, where the division rounds
+    /// towards zero like the ALU's `div`.
+    fn divide(&mut self, a: &Expression, b: &Expression) -> (Word, Word) {
+        let (a_bits, b_bits) = (self.encode(a), self.encode(b));
+        let zero = self.constant(0);
+        let (q, r) = (self.fresh_word(), self.fresh_word());
+
+        let b_is_zero = self.equal(&b_bits, &zero);
+        self.assert(!b_is_zero);
+        let product = self.mul(&q, &b_bits);
+        let sum = self.add(&product, &r);
+        let matches = self.equal(&sum, &a_bits);
+        self.assert(matches);
+
+        // The remainder takes the sign of a, and is smaller than b.
+        let a_negative = a_bits[self.width - 1];
+        let r_negative = r[self.width - 1];
+        let r_positive = self.less_than(&zero, &r);
+        let sign = self.or(a_negative, !r_negative);
+        self.assert(sign);
+        let sign = self.or(!a_negative, !r_positive);
+        self.assert(sign);
+        let (r_abs, b_abs) = (self.abs(&r), self.abs(&b_bits));
+        let smaller = self.less_than(&r_abs, &b_abs);
+        self.assert(smaller);
+
+        // |q| ≤ |a| / |b|, so that q * b can't wrap around for literal
+        // divisors.
+        let (a_bounds, b_bounds) = (a.bounds(), b.bounds());
+        let smallest_divisor = if b_bounds.min > 0 || b_bounds.max < 0 {
+            b_bounds.min.unsigned_abs().min(b_bounds.max.unsigned_abs())
+        } else {
+            1
+        };
+        let largest = (a_bounds.min.unsigned_abs().max(a_bounds.max.unsigned_abs())
+            / smallest_divisor)
+            .min(i64::MAX as u64) as i64;
+        let (low, high) = (self.constant(-largest), self.constant(largest));
+        let below = self.less_than(&q, &low);
+        let above = self.less_than(&high, &q);
+        self.assert(!below);
+        self.assert(!above);
+        (q, r)
+    }
+
+    fn encode(&mut self, expr: &Expression) -> Word {
+        let node = match expr {
+            Expression::Input(i) => return self.inputs[*i as usize].clone(),
+            Expression::Lit(n) => return self.constant(*n),
+            Expression::Instr(node) => node,
+        };
+        if let Some(word) = self.nodes.get(&node.id) {
+            return word.clone();
+        }
+        let word = match &node.instr {
+            Instruction::Inp(_) => panic!("inp cannot be part of an expression"),
+            Instruction::Add(a, b) => {
+                let (a, b) = (self.encode(a), self.encode(b));
+                self.add(&a, &b)
+            }
+            Instruction::Mul(a, b) => {
+                let (a, b) = (self.encode(a), self.encode(b));
+                self.mul(&a, &b)
+            }
+            Instruction::Div(a, b) => self.divide(a, b).0,
+            Instruction::Mod(a, b) => {
+                let (_, r) = self.divide(a, b);
+                // mod fails on negative operands.
+                let (a, b) = (self.encode(a), self.encode(b));
+                let zero = self.constant(0);
+                self.assert(!a[self.width - 1]);
+                let b_positive = self.less_than(&zero, &b);
+                self.assert(b_positive);
+                r
+            }
+            Instruction::Eql(a, b) => {
+                let (a, b) = (self.encode(a), self.encode(b));
+                let mut word = self.constant(0);
+                word[0] = self.equal(&a, &b);
+                word
+            }
+        };
+        self.nodes.insert(node.id, word.clone());
+        word
+    }
+}
+
+/// Find inputs, digits from 1 to 9, for which the expression evaluates to
+/// `target`. The digits in `fixed` that are `Some` are kept, its length is
+/// the number of inputs. `None` if there are no such inputs.
+pub(super) fn find_inputs(
+    expr: &Expression,
+    fixed: &[Option<i64>],
+    target: i64,
+) -> Result<Option<Vec<i64>>> {
+    let mut encoder = Encoder::new(width(expr, target), fixed.len());
+    for (i, digit) in fixed.iter().enumerate() {
+        if let Some(digit) = digit {
+            let (input, digit) = (encoder.inputs[i].clone(), encoder.constant(*digit));
+            let equal = encoder.equal(&input, &digit);
+            encoder.assert(equal);
+        }
+    }
+    let value = encoder.encode(expr);
+    let target_bits = encoder.constant(target);
+    let equal = encoder.equal(&value, &target_bits);
+    encoder.assert(equal);
+
+    // Only literal divisors are guaranteed not to wrap around, with others
+    // the solver may find inputs that don't actually give `target`. Those
+    // are blocked and the solver runs again.
+    loop {
+        let mut solver = rustsat_kissat::Kissat::default();
+        solver.add_cnf(encoder.instance.clone().into_cnf().0)?;
+        match solver.solve()? {
+            SolverResult::Sat => {}
+            SolverResult::Unsat => return Ok(None),
+            SolverResult::Interrupted => bail!("the SAT solver was interrupted"),
+        }
+
+        let mut digits = vec![];
+        let mut blocking = vec![];
+        for word in &encoder.inputs {
+            let mut digit = 0;
+            for (i, bit) in word.iter().enumerate() {
+                let set = match bit {
+                    Bit::Const(b) => *b,
+                    Bit::Var(l) => {
+                        let set = solver.lit_val(*l)? == TernaryVal::True;
+                        blocking.push(if set { !*l } else { *l });
+                        set
+                    }
+                };
+                digit |= (set as i64) << i;
+            }
+            digits.push(digit);
+        }
+        if expr.evaluate(&digits) == Some(target) {
+            return Ok(Some(digits));
+        }
+        encoder.instance.add_nary(&blocking);
+    }
+}
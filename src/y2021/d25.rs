@@ -1,5 +1,9 @@
 use anyhow::Result;
 
+use crate::automaton::{Automaton, Edges, Neighbourhood};
+use crate::grid::Grid;
+use crate::visualise::{self, Frame};
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 enum Field {
     Down,
@@ -7,9 +11,9 @@ enum Field {
     Empty,
 }
 
-type Board = Vec<Vec<Field>>;
+type Board = Grid<Field>;
 
-fn parse_input(input: &str) -> Board {
+fn parse_input(input: &str) -> Result<Board> {
     let mut out = vec![];
     for line in input.lines() {
         let mut outline = vec![];
@@ -28,101 +32,48 @@ fn parse_input(input: &str) -> Board {
         }
     }
 
-    for i in 1..out.len() {
-        if out[i].len() != out[i - 1].len() {
-            panic!(
-                "length of line {i} ({}) does not match length of line {} ({})",
-                out[i].len(),
-                i - 1,
-                out[i - 1].len()
-            );
-        }
-    }
-    out
+    Grid::from_rows(out)
 }
 
-fn step(board: &mut Board) -> bool {
-    let mut has_moved = false;
-
-    let last_x = board[0].len() - 1;
-    let last_y = board.len() - 1;
-
-    // horizontal motion
-    for line in board.iter_mut() {
-        let mut just_moved = false;
-        let mut wrap_moved = false;
-        for i in 0..line.len() {
-            let prev_idx = if i == 0 { last_x } else { i - 1 };
-            if !just_moved
-                && (i != last_x || !wrap_moved)
-                && line[i] == Field::Empty
-                && line[prev_idx] == Field::Right
-            {
-                has_moved = true;
-                just_moved = true;
-                if i == 0 {
-                    wrap_moved = true;
-                }
-                line.swap(prev_idx, i);
-            } else {
-                just_moved = false;
-            }
-        }
-    }
-
-    // vertical motion
-    for j in 0..board[0].len() {
-        let mut just_moved = false;
-        let mut wrap_moved = false;
-        for i in 0..board.len() {
-            let prev_idx = if i == 0 { last_y } else { i - 1 };
-            if !just_moved
-                && (i != last_y || !wrap_moved)
-                && board[i][j] == Field::Empty
-                && board[prev_idx][j] == Field::Down
-            {
-                has_moved = true;
-                just_moved = true;
-                if i == 0 {
-                    wrap_moved = true;
-                }
-                board[i][j] = Field::Down;
-                board[prev_idx][j] = Field::Empty;
-            } else {
-                just_moved = false;
-            }
+const EAST: Neighbourhood = Neighbourhood::Directional(&[(-1, 0), (1, 0)]);
+const SOUTH: Neighbourhood = Neighbourhood::Directional(&[(0, -1), (0, 1)]);
+
+/// Move the cucumbers of one herd if the field in front of them is empty.
+/// The neighbourhood contains the fields behind and in front of a field.
+fn move_herd(board: &mut Automaton<Field>, herd: Field, neighbourhood: &Neighbourhood) -> usize {
+    board.apply(neighbourhood, |&field, neighbours| {
+        match (field, neighbours.get(0), neighbours.get(1)) {
+            (Field::Empty, Some(&behind), _) if behind == herd => herd,
+            (f, _, Some(Field::Empty)) if f == herd => Field::Empty,
+            (f, _, _) => f,
         }
-    }
+    })
+}
 
-    has_moved
+/// Returns the number of fields that changed, two for every move.
+fn step(board: &mut Automaton<Field>) -> usize {
+    move_herd(board, Field::Right, &EAST) + move_herd(board, Field::Down, &SOUTH)
 }
 
-#[allow(unused)]
-fn print(board: &Board) {
-    for line in board {
-        for field in line {
-            print!(
-                "{}",
-                match field {
-                    Field::Down => "v",
-                    Field::Right => ">",
-                    Field::Empty => ".",
-                }
-            );
-        }
-        println!();
-    }
+fn frame(board: &Board) -> Frame {
+    Frame::grid(board, |field| match field {
+        Field::Down => 'v',
+        Field::Right => '>',
+        Field::Empty => '.',
+    })
 }
 
-pub fn solve(input: &str) -> Result<(i32, i32)> {
-    let mut board = parse_input(input);
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    let mut board = Automaton::new(parse_input(input)?, Edges::Wrapping);
 
-    let mut steps = 0;
-    while step(&mut board) {
-        steps += 1;
-    }
+    visualise::emit(|| frame(board.grid()));
+    let steps = board.run_until_stable(|board| {
+        let changed = step(board);
+        visualise::emit(|| frame(board.grid()));
+        changed
+    });
 
-    return Ok((steps + 1, 0));
+    Ok((steps, 0))
 }
 
 #[cfg(test)]
@@ -132,19 +83,20 @@ mod tests {
     fn test_parse_input() {
         let input = "v.\n>>\n";
         use Field::*;
-        let expected = vec![vec![Down, Empty], vec![Right, Right]];
+        let expected = Grid::from_rows(vec![vec![Down, Empty], vec![Right, Right]]).unwrap();
 
-        assert_eq!(parse_input(input), expected);
+        assert_eq!(parse_input(input).unwrap(), expected);
     }
 
     #[test]
     fn test_motion() {
         use Field::*;
-        let mut board = vec![vec![Right, Empty]];
-        assert!(step(&mut board));
-        assert_eq!(board, vec![vec![Empty, Right]]);
-        assert!(step(&mut board));
-        assert_eq!(board, vec![vec![Right, Empty]]);
+        let row = |fields: Vec<Field>| Grid::from_rows(vec![fields]).unwrap();
+        let mut board = Automaton::new(row(vec![Right, Empty]), Edges::Wrapping);
+        assert_eq!(step(&mut board), 2);
+        assert_eq!(board.grid(), &row(vec![Empty, Right]));
+        assert_eq!(step(&mut board), 2);
+        assert_eq!(board.grid(), &row(vec![Right, Empty]));
     }
 
     #[test]
@@ -161,10 +113,10 @@ mod tests {
             ..v....v>.
             ..........
         "#;
-        let mut board_in = parse_input(input);
-        let board_out = parse_input(output);
+        let mut board_in = Automaton::new(parse_input(input).unwrap(), Edges::Wrapping);
+        let board_out = parse_input(output).unwrap();
 
         step(&mut board_in);
-        assert_eq!(board_in, board_out);
+        assert_eq!(board_in.grid(), &board_out);
     }
 }
@@ -1,6 +1,8 @@
 use aho_corasick;
 use anyhow::Result;
 
+use crate::solution::Solution;
+
 fn parse_line_a(line: &str) -> (i32, i32) {
     let mut d1 = None;
     let mut d2 = 0;
@@ -40,14 +42,36 @@ fn parse_line_b(line: &str) -> (i32, i32) {
 }
 
 pub fn solve(input: &str) -> Result<(i32, i32)> {
-    let mut sum_a = 0;
-    let mut sum_b = 0;
-    for line in input.lines() {
-        let (d1_a, d2_a) = parse_line_a(line);
-        let (d1_b, d2_b) = parse_line_b(line);
-        sum_a += d1_a * 10 + d2_a;
-        sum_b += d1_b * 10 + d2_b;
+    let lines = Day::parse(input)?;
+    Ok((Day::part1(&lines)?, Day::part2(&lines)?))
+}
+
+fn calibration_sum(lines: &[String], parse_line: impl Fn(&str) -> (i32, i32)) -> i32 {
+    lines
+        .iter()
+        .map(|line| {
+            let (d1, d2) = parse_line(line);
+            d1 * 10 + d2
+        })
+        .sum()
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<String>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(str::to_owned).collect())
     }
 
-    Ok((sum_a, sum_b))
+    fn part1(lines: &Self::Input) -> Result<i32> {
+        Ok(calibration_sum(lines, parse_line_a))
+    }
+
+    fn part2(lines: &Self::Input) -> Result<i32> {
+        Ok(calibration_sum(lines, parse_line_b))
+    }
 }
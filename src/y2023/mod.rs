@@ -1,10 +1,5 @@
 pub mod d01;
 
-#[cfg(test)]
-mod tests {
-    use crate::test_task;
+use crate::{solution, solution::Entry};
 
-    test_task! { y2023, d01, example_a, (142, _) }
-    test_task! { y2023, d01, example_b, (_, 281) }
-    test_task! { y2023, d01, full, (55607, 55291) }
-}
+pub const SOLUTIONS: &[Entry] = &[solution!(2023, 1, d01)];
@@ -1,24 +1,34 @@
 use anyhow::Result;
 
+use crate::solution::Solution;
+
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let mut a = 0;
-    let mut b = 0;
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
-        }
-
-        let battery_bank: Vec<_> = line
-            .as_bytes()
-            .into_iter()
-            .map(|b| i64::from(b - b'0'))
-            .collect();
-
-        a += max_joltage(2, &battery_bank);
-        b += max_joltage(12, &battery_bank);
+    let battery_banks = Day::parse(input)?;
+    Ok((Day::part1(&battery_banks)?, Day::part2(&battery_banks)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<Vec<i64>>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.bytes().map(|b| i64::from(b - b'0')).collect())
+            .collect())
+    }
+
+    fn part1(battery_banks: &Self::Input) -> Result<i64> {
+        Ok(battery_banks.iter().map(|bank| max_joltage(2, bank)).sum())
     }
 
-    Ok((a, b))
+    fn part2(battery_banks: &Self::Input) -> Result<i64> {
+        Ok(battery_banks.iter().map(|bank| max_joltage(12, bank)).sum())
+    }
 }
 
 fn max_joltage(nr_batteries: usize, battery_bank: &[i64]) -> i64 {
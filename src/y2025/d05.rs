@@ -1,21 +1,48 @@
 use std::ops::RangeInclusive;
 
 use anyhow::Result;
+
+use crate::solution::Solution;
+
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let (ranges, ingredients) = parse(input);
+    let (ranges, ingredients) = Day::parse(input)?;
+    Ok((fresh_ingredients(&ranges, &ingredients), fresh_ids(&ranges)))
+}
 
-    let ranges = merge_ranges(ranges);
+pub struct Day;
 
-    let a = ingredients
-        .into_iter()
-        .filter(|ing| ranges.iter().any(|range| range.contains(ing)))
-        .count();
+impl Solution for Day {
+    type Input = (Vec<RangeInclusive<i64>>, Vec<i64>);
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let (ranges, ingredients) = parse(input);
+        Ok((merge_ranges(ranges), ingredients))
+    }
+
+    fn part1((ranges, ingredients): &Self::Input) -> Result<i64> {
+        Ok(fresh_ingredients(ranges, ingredients))
+    }
+
+    fn part2((ranges, _): &Self::Input) -> Result<i64> {
+        Ok(fresh_ids(ranges))
+    }
+}
 
-    let b = ranges
+/// Number of available ingredients that fall into one of the (merged) ranges
+fn fresh_ingredients(ranges: &[RangeInclusive<i64>], ingredients: &[i64]) -> i64 {
+    ingredients
         .iter()
-        .fold(0, |acc, e| acc + e.end() + 1 - e.start());
+        .filter(|ing| ranges.iter().any(|range| range.contains(ing)))
+        .count() as i64
+}
 
-    Ok((a as i64, b as i64))
+/// Number of ids covered by the (merged) ranges
+fn fresh_ids(ranges: &[RangeInclusive<i64>]) -> i64 {
+    ranges
+        .iter()
+        .fold(0, |acc, e| acc + e.end() + 1 - e.start())
 }
 
 fn merge_ranges(mut ranges: Vec<RangeInclusive<i64>>) -> Vec<RangeInclusive<i64>> {
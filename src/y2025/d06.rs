@@ -1,22 +1,46 @@
 use anyhow::Result;
 use itertools::Itertools;
 
+use crate::solution::Solution;
+
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    Ok((
-        solve_problems(parse_a(input)),
-        solve_problems(parse_b(input)),
-    ))
+    let problems = Day::parse(input)?;
+    Ok((Day::part1(&problems)?, Day::part2(&problems)?))
+}
+
+type Problems = Vec<(Vec<i64>, Op)>;
+
+pub struct Day;
+
+impl Solution for Day {
+    /// The problems read by rows for the first part, and by columns for the
+    /// second one.
+    type Input = (Problems, Problems);
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok((parse_a(input), parse_b(input)))
+    }
+
+    fn part1((problems, _): &Self::Input) -> Result<i64> {
+        Ok(solve_problems(problems))
+    }
+
+    fn part2((_, problems): &Self::Input) -> Result<i64> {
+        Ok(solve_problems(problems))
+    }
 }
 
-fn solve_problems(problems: Vec<(Vec<i64>, Op)>) -> i64 {
+fn solve_problems(problems: &[(Vec<i64>, Op)]) -> i64 {
     problems
-        .into_iter()
-        .map(|(nums, op)| nums.into_iter().reduce(|a, b| op.apply(a, b)).unwrap())
+        .iter()
+        .map(|(nums, op)| nums.iter().copied().reduce(|a, b| op.apply(a, b)).unwrap())
         .sum()
 }
 
 #[derive(PartialEq, Eq, Debug)]
-enum Op {
+pub enum Op {
     Plus,
     Times,
 }
@@ -1,20 +1,21 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+
+use crate::grid::Grid;
 
 const START: u8 = b'S';
 const SPLIT: u8 = b'^';
 
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let lines: Vec<&[u8]> = input.lines().map(|line| line.as_bytes()).collect();
+    let map = Grid::parse(input)?;
+    let (start_x, start_y) = map.find(&START).context("No start position found")?;
 
-    let mut beams: Vec<i64> = lines[0]
-        .iter()
-        .map(|c| if *c == START { 1 } else { 0 })
-        .collect();
+    let mut beams = vec![0i64; map.width()];
+    beams[start_x] = 1;
 
     // number of splits
     let mut a = 0;
 
-    for line in &lines[2..] {
+    for line in map.rows().skip(start_y + 1) {
         for (i, c) in line.iter().enumerate() {
             if c == &SPLIT && beams[i] > 0 {
                 assert!(i > 0);
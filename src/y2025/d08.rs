@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use anyhow::Result;
 
+use crate::union_find::{SetIndex, UnionFind};
+
 pub fn solve(input: &str) -> Result<(i64, i64)> {
     let coords = parse(input);
 
@@ -16,7 +18,7 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
     }
 
     // run union find
-    let mut uf = UnionFind::new(coords.len());
+    let mut uf = UnionFind::with_sets(coords.len());
 
     let number_of_connections = if coords.len() < 1000 {
         10 // example
@@ -25,25 +27,25 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
     };
 
     let closest_pairs: Vec<_> = distances.into_values().collect();
-    for (i, j) in &closest_pairs[..number_of_connections] {
-        uf.unify(*i, *j);
+    for &(i, j) in &closest_pairs[..number_of_connections] {
+        uf.union(SetIndex::from(i), SetIndex::from(j));
     }
 
-    assert!(uf.nr_groups() > 1, "groups should not yet be all connected");
+    assert!(uf.nr_sets() > 1, "groups should not yet be all connected");
 
-    let mut group_sizes = uf.group_sizes();
+    let mut group_sizes: Vec<_> = uf.sizes().map(|size| size as i64).collect();
     group_sizes.sort_unstable();
     group_sizes.reverse();
     let task_a = group_sizes.into_iter().take(3).product();
 
     let mut task_b = None;
     // do the remaining connections
-    for (i, j) in &closest_pairs[number_of_connections..] {
-        uf.unify(*i, *j);
-        if uf.nr_groups() == 1 {
+    for &(i, j) in &closest_pairs[number_of_connections..] {
+        uf.union(SetIndex::from(i), SetIndex::from(j));
+        if uf.nr_sets() == 1 {
             // this connection has merged the last groups
-            let xi = i64::from(coords[*i].x);
-            let xj = i64::from(coords[*j].x);
+            let xi = i64::from(coords[i].x);
+            let xj = i64::from(coords[j].x);
             task_b = Some(xi * xj);
             break;
         }
@@ -52,73 +54,6 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
     Ok((task_a, task_b.unwrap()))
 }
 
-struct UnionFind {
-    parents: Vec<Option<usize>>,
-    group_size: Vec<i64>,
-
-    nr_groups: usize,
-}
-
-impl UnionFind {
-    pub fn new(size: usize) -> Self {
-        Self {
-            parents: vec![None; size],
-            group_size: vec![1; size],
-            nr_groups: size,
-        }
-    }
-
-    /// Merge two groups
-    pub fn unify(&mut self, a: usize, b: usize) {
-        let a = self.find_root(a);
-        let b = self.find_root(b);
-        if a != b {
-            self.parents[a] = Some(b);
-            self.group_size[b] += self.group_size[a];
-            self.nr_groups -= 1;
-        }
-    }
-
-    /// how many yet disconnected components does the graph have?
-    pub fn nr_groups(&self) -> usize {
-        self.nr_groups
-    }
-
-    /// Return the sizes of the goups. Note that the order in which the sizes
-    /// are returned is implementation defined and should not be relied upon.
-    pub fn group_sizes(&self) -> Vec<i64> {
-        self.group_size
-            .iter()
-            .zip(self.parents.iter())
-            .filter_map(|(size, parent)| {
-                if parent.is_none() {
-                    // this is a root node
-                    Some(*size)
-                } else {
-                    // we're not at a root node, skip this node
-                    None
-                }
-            })
-            .collect()
-    }
-
-    fn find_root(&mut self, node: usize) -> usize {
-        match self.parents[node] {
-            Some(p) => {
-                let root = self.find_root(p);
-
-                if root != node {
-                    // Update the parent of the current node to point directly to the
-                    // root in order to shorten the chain.
-                    self.parents[node] = Some(root);
-                }
-                root
-            }
-            None => node,
-        }
-    }
-}
-
 /// A 3D cartesian coordinate
 #[derive(Clone, Copy)]
 struct Coord {
@@ -2,10 +2,35 @@ use std::{collections::BTreeMap, ops::Range};
 
 use anyhow::Result;
 
+use crate::solution::Solution;
+
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let red_tiles = parse(input);
+    let red_tiles = Day::parse(input)?;
+    Ok((Day::part1(&red_tiles)?, Day::part2(&red_tiles)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<(i64, i64)>;
+    type Part1 = i64;
+    type Part2 = i64;
 
-    // Solve part 1    O(N²)
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input))
+    }
+
+    fn part1(red_tiles: &Self::Input) -> Result<i64> {
+        Ok(largest_area(red_tiles))
+    }
+
+    fn part2(red_tiles: &Self::Input) -> Result<i64> {
+        Ok(largest_redgreen_area(red_tiles))
+    }
+}
+
+fn largest_area(red_tiles: &[(i64, i64)]) -> i64 {
+    // O(N²)
     let mut largest_area = 1;
     for (i, (x, y)) in red_tiles.iter().enumerate() {
         for (ox, oy) in &red_tiles[..i] {
@@ -16,7 +41,11 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
         }
     }
 
-    // Solve part 2     O(N²log²N)
+    largest_area
+}
+
+fn largest_redgreen_area(red_tiles: &[(i64, i64)]) -> i64 {
+    // O(N²log²N)
     //
     // The idea is to use a 2d Fenwick tree to query the filled-in area
     // for all rectangles, and only consider the rectangles where the
@@ -91,7 +120,7 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
         }
     }
 
-    Ok((largest_area, largest_redgreen_area))
+    largest_redgreen_area
 }
 
 /// Compress coordinates by moving everything together
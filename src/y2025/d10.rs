@@ -3,18 +3,45 @@ use std::{collections::VecDeque, i64};
 use anyhow::Result;
 use microlp::{ComparisonOp, OptimizationDirection, Problem};
 
+use crate::solution::Solution;
+
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let machines = parse(input);
+    let machines = Day::parse(input)?;
+    Ok((Day::part1(&machines)?, Day::part2(&machines)?))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    type Input = Vec<Machine>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input))
+    }
+
+    fn part1(machines: &Self::Input) -> Result<i64> {
+        Ok(total_config_presses(machines))
+    }
+
+    fn part2(machines: &Self::Input) -> Result<i64> {
+        Ok(total_joltage_presses(machines))
+    }
+}
 
-    // solve part 1
+fn total_config_presses(machines: &[Machine]) -> i64 {
     let mut total_config_presses = 0;
-    for machine in &machines {
+    for machine in machines {
         let best =
             bfs(0, machine.indicator_target, &machine.buttons_masks).expect("unsolvable machine");
         total_config_presses += best;
     }
 
-    // solve part 2
+    total_config_presses
+}
+
+fn total_joltage_presses(machines: &[Machine]) -> i64 {
     let mut total_joltage_presses = 0;
     for machine in machines {
         let mut problem = Problem::new(OptimizationDirection::Minimize);
@@ -48,7 +75,7 @@ pub fn solve(input: &str) -> Result<(i64, i64)> {
         total_joltage_presses += objective.round() as i64;
     }
 
-    Ok((total_config_presses, total_joltage_presses))
+    total_joltage_presses
 }
 
 fn bfs(start: u16, target: u16, buttons: &[u16]) -> Option<i64> {
@@ -77,7 +104,7 @@ fn bfs(start: u16, target: u16, buttons: &[u16]) -> Option<i64> {
     None
 }
 
-struct Machine {
+pub struct Machine {
     indicator_target: u16,
     buttons_indices: Vec<Vec<usize>>,
     buttons_masks: Vec<u16>,
@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 
+use crate::solution::Solution;
+
 type Node = [u8; 3];
 const YOU: Node = [b'y', b'o', b'u'];
 const SVR: Node = [b's', b'v', b'r'];
@@ -10,9 +12,28 @@ const DAC: Node = [b'd', b'a', b'c'];
 const FFT: Node = [b'f', b'f', b't'];
 
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let graph = parse(input);
+    let graph = Day::parse(input)?;
+    Ok((Day::part1(&graph)?, Day::part2(&graph)?))
+}
+
+pub struct Day;
 
-    Ok((solve_part_1(&graph), solve_part_2(&graph)))
+impl Solution for Day {
+    type Input = HashMap<Node, Vec<Node>>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input))
+    }
+
+    fn part1(graph: &Self::Input) -> Result<i64> {
+        Ok(solve_part_1(graph))
+    }
+
+    fn part2(graph: &Self::Input) -> Result<i64> {
+        Ok(solve_part_2(graph))
+    }
 }
 
 fn solve_part_1(graph: &HashMap<Node, Vec<Node>>) -> i64 {
@@ -6,31 +6,54 @@ use rustsat::solvers::{Solve, SolverResult};
 use rustsat::types::constraints::CardConstraint;
 use rustsat::types::{Lit, TernaryVal};
 
+use crate::grid::Grid;
+use crate::solution::{Solution, Unsolved};
+use crate::visualise::{self, Frame};
+
 const NR_SHAPES: usize = 6;
-const PRINT_SOLUTION: bool = false;
 
 pub fn solve(input: &str) -> Result<(i64, i64)> {
-    let (shapes, problems) = parse(input);
+    let parsed = Day::parse(input)?;
+    Ok((Day::part1(&parsed)?, 0))
+}
 
-    let mut solvables = 0;
+pub struct Day;
 
-    // find all flips and rotations for every shape
-    let shapesets: Vec<_> = shapes
-        .into_iter()
-        .map(|shape| shape.all_variations())
-        .collect();
+impl Solution for Day {
+    /// All flips and rotations of every shape, and the problems.
+    type Input = (Vec<Vec<Shape>>, Vec<Problem>);
+    type Part1 = i64;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let (shapes, problems) = parse(input);
+
+        // find all flips and rotations for every shape
+        let shapesets = shapes
+            .into_iter()
+            .map(|shape| shape.all_variations())
+            .collect();
+
+        Ok((shapesets, problems))
+    }
 
-    for problem in problems {
-        if problem.is_solvable(&shapesets) {
-            solvables += 1;
+    fn part1((shapesets, problems): &Self::Input) -> Result<i64> {
+        let mut solvables = 0;
+        for problem in problems {
+            if problem.is_solvable(shapesets) {
+                solvables += 1;
+            }
         }
+        Ok(solvables)
     }
 
-    Ok((solvables, 0))
+    fn part2(_: &Self::Input) -> Result<Unsolved> {
+        Ok(Unsolved)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
-struct Shape {
+pub struct Shape {
     grid: [[bool; 3]; 3],
 }
 
@@ -104,13 +127,13 @@ impl Shape {
     }
 }
 
-struct Problem {
+pub struct Problem {
     dim: (i32, i32),
     counts: Vec<i32>,
 }
 
 impl Problem {
-    pub fn is_solvable(self, shapesets: &[Vec<Shape>]) -> bool {
+    pub fn is_solvable(&self, shapesets: &[Vec<Shape>]) -> bool {
         let area = self.dim.0 * self.dim.1;
 
         let required_area: i32 = self
@@ -177,16 +200,14 @@ impl Problem {
         let res = solver.solve().unwrap();
         dbg!(res);
 
-        // print the solution with letters like in the task description
-        if PRINT_SOLUTION {
+        // show the solution with letters like in the task description
+        if visualise::enabled() {
             for sl in &mut shape_literals {
                 sl.reverse();
             }
 
             if let Ok(sol) = solver.full_solution() {
-                let mut grid: Vec<_> = (0..self.dim.1)
-                    .map(|_| vec![b'.'; self.dim.0 as usize])
-                    .collect();
+                let mut grid = Grid::new(self.dim.0 as usize, self.dim.1 as usize, '.');
 
                 let mut char = b'A';
                 for y in 2..self.dim.1 {
@@ -199,11 +220,11 @@ impl Problem {
                                     for sy in 0..3 {
                                         for sx in 0..3 {
                                             if shape.get(sx, sy) == Some(true) {
-                                                let gx = x - 2 + sx;
-                                                let gy = y - 2 + sy;
+                                                let pos =
+                                                    ((x - 2 + sx) as usize, (y - 2 + sy) as usize);
 
-                                                assert_eq!(grid[gy as usize][gx as usize], b'.');
-                                                grid[gy as usize][gx as usize] = char;
+                                                assert_eq!(grid[pos], '.');
+                                                grid[pos] = char as char;
                                             }
                                         }
                                     }
@@ -214,13 +235,7 @@ impl Problem {
                     }
                 }
 
-                for row in grid {
-                    for char in row {
-                        print!("{}", str::from_utf8(&[char]).unwrap());
-                    }
-
-                    println!();
-                }
+                visualise::emit(|| Frame::Grid(grid));
             }
         }
 
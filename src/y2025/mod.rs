@@ -11,44 +11,19 @@ pub mod d10;
 pub mod d11;
 pub mod d12;
 
-#[cfg(test)]
-mod tests {
-    use crate::test_task;
-
-    test_task! { y2025, d01, example, (3, 6) }
-    test_task! { y2025, d01, full, (1071, 6700) }
-
-    test_task! { y2025, d02, example, (1227775554, 4174379265) }
-    test_task! { y2025, d02, full, (41294979841, 66500947346) }
-
-    test_task! { y2025, d03, example, (357, 3121910778619) }
-    test_task! { y2025, d03, full, (17408, 172740584266849) }
-
-    test_task! { y2025, d04, example, (13, 43) }
-    test_task! { y2025, d04, full, (1602, 9518) }
-
-    test_task! { y2025, d05, example, (3, 14) }
-    test_task! { y2025, d05, full, (798, 366181852921027) }
-
-    test_task! { y2025, d06, example, (4277556, 3263827) }
-    test_task! { y2025, d06, full, (6725216329103, 10600728112865) }
-
-    test_task! { y2025, d07, example, (21, 40) }
-    test_task! { y2025, d07, full, (1656, 76624086587804) }
-
-    test_task! { y2025, d08, example, (40, 25272) }
-    test_task! { y2025, d08, full, (75582, 59039696) }
-
-    test_task! { y2025, d09, example, (50, 24) }
-    test_task! { y2025, d09, full, (4752484112, 1465767840) }
-
-    test_task! { y2025, d10, example, (7, 33) }
-    test_task! { y2025, d10, full, (507, 18981) }
-
-    test_task! { y2025, d11, example_a, (5, _) }
-    test_task! { y2025, d11, example_b, (_, 2) }
-    test_task! { y2025, d11, full, (701, 390108778818526) }
-
-    test_task! { y2025, d12, example, (2, _) }
-    test_task! { y2025, d12, full, (440, _) }
-}
+use crate::{solution, solution::Entry};
+
+pub const SOLUTIONS: &[Entry] = &[
+    solution!(2025, 1, d01, str),
+    solution!(2025, 2, d02, str),
+    solution!(2025, 3, d03),
+    solution!(2025, 4, d04, str),
+    solution!(2025, 5, d05),
+    solution!(2025, 6, d06),
+    solution!(2025, 7, d07, str),
+    solution!(2025, 8, d08, str),
+    solution!(2025, 9, d09),
+    solution!(2025, 10, d10),
+    solution!(2025, 11, d11),
+    solution!(2025, 12, d12),
+];
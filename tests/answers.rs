@@ -0,0 +1,188 @@
+//! Runs every registered solution on every input in `data/` and compares the
+//! results with the answers recorded in `data/{year}/answers.toml`.
+//!
+//! Mismatches, failing solutions and recorded answers without an input file
+//! fail the run. Inputs without recorded answers, or without a registered
+//! solution, are only reported.
+//!
+//! Like with the default test harness, the run can be restricted with a
+//! filter, e.g. `cargo test --test answers -- y2021::d16`.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use advent::answers::{self, Expected, Input, Manifest};
+use advent::io::file_str;
+use advent::solution;
+use anyhow::Result;
+
+const DATA_DIR: &str = "data";
+
+struct Case {
+    input: Input,
+    expected: Expected,
+}
+
+impl Case {
+    fn name(&self) -> String {
+        format!(
+            "y{}::d{:02}::{}",
+            self.input.year, self.input.day, self.input.suffix
+        )
+    }
+
+    /// Run the parts of the solution that have recorded answers, returning
+    /// a description of every wrong answer.
+    fn run(&self) -> Result<Vec<String>> {
+        let entry = solution::find(self.input.year, self.input.day)
+            .expect("cases are only created for registered solutions");
+        let parsed = entry.parse(&file_str(&self.input.path)?)?;
+
+        let mut wrong = vec![];
+        for (part, expected) in [(1, &self.expected.part1), (2, &self.expected.part2)] {
+            if let Some(expected) = expected {
+                let actual = match part {
+                    1 => entry.part1(&parsed)?,
+                    _ => entry.part2(&parsed)?,
+                };
+                if Some(expected) != actual.as_ref() {
+                    wrong.push(format!(
+                        "part {}: expected {}, got {}",
+                        part,
+                        expected,
+                        actual.as_deref().unwrap_or("no answer")
+                    ));
+                }
+            }
+        }
+        Ok(wrong)
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    cases: Vec<Case>,
+    missing_answers: Vec<String>,
+    missing_inputs: Vec<String>,
+    missing_solutions: Vec<String>,
+}
+
+fn collect(filter: &[String]) -> Result<Report> {
+    let selected = |name: &str| filter.is_empty() || filter.iter().any(|f| name.contains(f));
+
+    let mut report = Report::default();
+    for year in answers::years(DATA_DIR)? {
+        let mut manifest = Manifest::load(DATA_DIR, year)?;
+
+        for input in answers::inputs(DATA_DIR, year)? {
+            let expected = manifest.answers.remove(&(input.day, input.suffix.clone()));
+            let name = format!("y{}::d{:02}::{}", year, input.day, input.suffix);
+            if !selected(&name) {
+                continue;
+            }
+
+            match (solution::find(year, input.day), expected) {
+                (None, _) => report.missing_solutions.push(name),
+                (Some(_), None) => report.missing_answers.push(name),
+                (Some(_), Some(expected)) => report.cases.push(Case { input, expected }),
+            }
+        }
+
+        // Everything left in the manifest has no corresponding input file.
+        for (day, suffix) in manifest.answers.into_keys() {
+            let name = format!("y{}::d{:02}::{}", year, day, suffix);
+            if selected(&name) {
+                report.missing_inputs.push(name);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn print_list(title: &str, names: &[String]) {
+    if !names.is_empty() {
+        println!("\n{} ({}):", title, names.len());
+        for name in names {
+            println!("    {}", name);
+        }
+    }
+}
+
+fn main() -> Result<ExitCode> {
+    let mut filter = vec![];
+    let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+    for arg in std::env::args().skip(1) {
+        if let Some(n) = arg.strip_prefix("--test-threads=") {
+            threads = n.parse()?;
+        } else if !arg.starts_with('-') {
+            filter.push(arg);
+        }
+    }
+
+    let report = collect(&filter)?;
+    println!("\nrunning {} answer checks", report.cases.len());
+
+    let next = AtomicUsize::new(0);
+    let mismatches = Mutex::new(vec![]);
+    thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            s.spawn(|| {
+                while let Some(case) = report.cases.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let name = case.name();
+                    let problems = match panic::catch_unwind(AssertUnwindSafe(|| case.run())) {
+                        Ok(Ok(wrong)) => wrong,
+                        Ok(Err(e)) => vec![format!("error: {:#}", e)],
+                        Err(_) => vec!["panicked".to_owned()],
+                    };
+                    println!(
+                        "test {} ... {}",
+                        name,
+                        if problems.is_empty() { "ok" } else { "FAILED" }
+                    );
+                    if !problems.is_empty() {
+                        mismatches.lock().unwrap().push((name, problems));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut mismatches = mismatches.into_inner().unwrap();
+    mismatches.sort();
+
+    print_list("inputs without recorded answers", &report.missing_answers);
+    print_list(
+        "inputs without a registered solution",
+        &report.missing_solutions,
+    );
+    print_list(
+        "recorded answers without an input file",
+        &report.missing_inputs,
+    );
+    if !mismatches.is_empty() {
+        println!("\nmismatches ({}):", mismatches.len());
+        for (name, problems) in &mismatches {
+            for problem in problems {
+                println!("    {}: {}", name, problem);
+            }
+        }
+    }
+
+    let failed = mismatches.len() + report.missing_inputs.len();
+    println!(
+        "\nanswer check result: {}. {} passed; {} failed",
+        if failed == 0 { "ok" } else { "FAILED" },
+        report.cases.len() - mismatches.len(),
+        failed
+    );
+
+    Ok(if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}